@@ -0,0 +1,255 @@
+// Slow closures run off the main thread. `run` gives a closure a thread of its
+// own, and a `Pool` shares a few threads between many closures, in the manner
+// of the book's web server
+// (https://doc.rust-lang.org/book/ch21-02-multithreaded.html). Either way the
+// caller gets a `Task` to poll, wait on for a while, or cancel.
+//
+// A closure that panics only fails its own task where panics unwind, as they
+// do under `cargo test`. This crate's binaries are built with panic = 'abort'
+// (see Cargo.toml), so there a panicking closure ends the whole process, and
+// no task ever sees `TaskError::Panicked`.
+use std::error::Error;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TaskError {
+    Cancelled,
+    // The closure panicked, so there is no value. Only seen where panics
+    // unwind; with panic = 'abort' the process ends instead.
+    Panicked,
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskError::Cancelled => write!(f, "the task was cancelled"),
+            TaskError::Panicked => write!(f, "the task panicked"),
+        }
+    }
+}
+
+impl Error for TaskError {}
+
+// Handed to the closure, which can check it to give up early. Closures that
+// never look still run to the end, but their value is thrown away.
+#[derive(Debug, Clone, Default)]
+pub struct Cancel {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Cancel {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+pub struct Task<T> {
+    value: Receiver<T>,
+    cancel: Cancel,
+    // Once known
+    result: Option<Result<T, TaskError>>,
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// The task, and the job that does its work and reports back to it. The job
+// sends nothing if it is cancelled before it starts, or if the closure panics.
+fn task<T, F>(closure: F) -> (Task<T>, Job)
+where
+    T: Send + 'static,
+    F: FnOnce(&Cancel) -> T + Send + 'static,
+{
+    let (sender, value) = mpsc::channel();
+    let cancel = Cancel::default();
+
+    let job_cancel = cancel.clone();
+    let job = Box::new(move || {
+        if !job_cancel.is_cancelled() {
+            // The task may have been dropped, and nobody wants the value
+            let _ = sender.send(closure(&job_cancel));
+        }
+    });
+
+    let task = Task {
+        value,
+        cancel,
+        result: None,
+    };
+    (task, job)
+}
+
+// Runs the closure on a thread of its own
+pub fn run<T, F>(closure: F) -> Task<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Cancel) -> T + Send + 'static,
+{
+    let (task, job) = task(closure);
+    thread::spawn(job);
+    task
+}
+
+impl<T> Task<T> {
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    // For cancelling the task from somewhere else, such as another thread
+    pub fn canceller(&self) -> Cancel {
+        self.cancel.clone()
+    }
+
+    // The sender is only dropped without sending if the job never ran the
+    // closure to the end
+    fn stopped(&self) -> TaskError {
+        if self.cancel.is_cancelled() {
+            TaskError::Cancelled
+        } else {
+            TaskError::Panicked
+        }
+    }
+
+    // Cancelling settles a task straight away, unless it had already finished
+    fn settle(&mut self, received: Result<T, RecvTimeoutError>) -> bool {
+        if self.result.is_none() {
+            self.result = match received {
+                _ if self.cancel.is_cancelled() => Some(Err(TaskError::Cancelled)),
+                Ok(value) => Some(Ok(value)),
+                Err(RecvTimeoutError::Disconnected) => Some(Err(self.stopped())),
+                Err(RecvTimeoutError::Timeout) => None,
+            };
+        }
+        self.result.is_some()
+    }
+
+    // True once the task has finished or been cancelled, without waiting
+    pub fn poll(&mut self) -> bool {
+        if self.result.is_some() {
+            return true;
+        }
+        let received = self.value.try_recv().map_err(|e| match e {
+            TryRecvError::Empty => RecvTimeoutError::Timeout,
+            TryRecvError::Disconnected => RecvTimeoutError::Disconnected,
+        });
+        self.settle(received)
+    }
+
+    // Like `poll`, but gives the task up to `timeout` to finish
+    pub fn wait_timeout(&mut self, timeout: Duration) -> bool {
+        if self.result.is_some() || self.cancel.is_cancelled() {
+            return self.poll();
+        }
+        let received = self.value.recv_timeout(timeout);
+        self.settle(received)
+    }
+
+    // Blocks until the task finishes. A task cancelled from another thread in
+    // the meantime is only noticed once its closure returns.
+    pub fn wait(mut self) -> Result<T, TaskError> {
+        if !self.poll() {
+            let received = self
+                .value
+                .recv()
+                .map_err(|_| RecvTimeoutError::Disconnected);
+            self.settle(received);
+        }
+        self.result.expect("a settled task has a result")
+    }
+}
+
+impl<T> fmt::Debug for Task<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Task")
+            .field("cancelled", &self.cancel.is_cancelled())
+            .field("finished", &self.result.is_some())
+            .finish()
+    }
+}
+
+// A fixed number of threads, each running one closure after another in the
+// order they were given. Dropping the pool waits for every closure to finish.
+pub struct Pool {
+    workers: Vec<JoinHandle<()>>,
+    jobs: Option<mpsc::Sender<Job>>,
+}
+
+impl Pool {
+    // Panics if there are no threads, as nothing would ever run
+    pub fn new(threads: usize) -> Pool {
+        assert!(threads > 0, "a pool needs at least one thread");
+
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let queue = Arc::new(Mutex::new(queue));
+        let workers = (0..threads)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    loop {
+                        // The lock is only held while taking a job, not while running it
+                        let job = queue.lock().unwrap_or_else(|e| e.into_inner()).recv();
+                        match job {
+                            // A closure that panics loses its task's value, not the thread
+                            Ok(job) => {
+                                let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                            }
+                            // The pool has been dropped
+                            Err(_) => break,
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        Pool {
+            workers,
+            jobs: Some(jobs),
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    // Queues the closure for the next free thread. A task cancelled while it
+    // is still queued never runs.
+    pub fn run<T, F>(&self, closure: F) -> Task<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Cancel) -> T + Send + 'static,
+    {
+        let (task, job) = task(closure);
+        self.jobs
+            .as_ref()
+            .expect("jobs are only closed when the pool is dropped")
+            .send(job)
+            .expect("the workers outlive the pool");
+        task
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        drop(self.jobs.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl fmt::Debug for Pool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("threads", &self.workers.len())
+            .finish_non_exhaustive()
+    }
+}
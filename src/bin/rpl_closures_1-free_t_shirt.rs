@@ -1,54 +1,87 @@
+use std::env;
+use std::process;
 use std::thread;
 use std::time::Duration;
 
-#[derive(Debug, Copy, Clone)]
-enum ShirtColour {
-    Red,
-    Blue
-}
+use rusty_stuff::background::Pool;
+use rusty_stuff::cacher::Cacher;
+use rusty_stuff::inventory::shared::SharedInventory;
+use rusty_stuff::inventory::{GiveawayError, Inventory, Preference, Shirt};
 
-struct Inventory {
-    shirts: Vec<ShirtColour>,
-}
+// The book's two blue shirts and a red one, unless others are given on the
+// command line as colour/size/style, e.g. green/L/v-neck
+const BOOK_STOCK: [&str; 3] = ["blue/M/crew", "red/M/crew", "blue/M/crew"];
+
+const CROWD: usize = 25;
 
-impl Inventory {
-    fn giveaway(&self, user_preference: Option<ShirtColour>) -> ShirtColour {
-        user_preference.unwrap_or_else(|| self.most_stocked())
+fn report(user_preference: &Preference, giveaway: Result<Shirt, GiveawayError>) {
+    match giveaway {
+        Ok(shirt) => println!("The user with preference {user_preference} gets {shirt}"),
+        Err(e) => println!("The user with preference {user_preference} gets nothing: {e}"),
     }
+}
 
-    fn most_stocked(&self) -> ShirtColour {
-        let mut num_red = 0;
-        let mut num_blue = 0;
+// https://doc.rust-lang.org/book/ch13-0    1-closures.html
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let skus: Vec<&str> = if args.is_empty() {
+        BOOK_STOCK.to_vec()
+    } else {
+        args.iter().map(String::as_str).collect()
+    };
 
-        for colour in &self.shirts {
-            match colour {
-                ShirtColour::Red => num_red += 1,
-                ShirtColour::Blue => num_blue += 1,
+    let mut store = Inventory::new();
+    for sku in skus {
+        match sku.parse::<Shirt>() {
+            Ok(shirt) => store.restock(shirt, 1).expect("room for one more shirt"),
+            Err(e) => {
+                eprintln!("Can't stock {sku:?}: {e}");
+                process::exit(2);
             }
         }
-
-        if num_red > num_blue {
-            ShirtColour::Red
-        } else {
-            ShirtColour::Blue
-        }
     }
-}
 
-// https://doc.rust-lang.org/book/ch13-0    1-closures.html
-fn main() {
-    let store = Inventory { shirts: vec![ShirtColour::Blue, ShirtColour::Red, ShirtColour::Blue] };
+    let user_pref1 = Preference::any().colour("red");
+    let giveaway1 = store.giveaway(&user_pref1);
+    report(&user_pref1, giveaway1);
 
-    let user_pref1 = Some(ShirtColour::Red);
-    let giveaway1 = store.giveaway(user_pref1);
-    println!(
-        "The user with preference {:?} gets {:?}", user_pref1, giveaway1
-    );
+    let user_pref2 = Preference::any();
+    let giveaway2 = store.giveaway(&user_pref2);
+    report(&user_pref2, giveaway2);
+
+    // The only red shirt went to the first user
+    let user_pref3 = Preference::any().colour("red");
+    let giveaway3 = store.giveaway(&user_pref3);
+    report(&user_pref3, giveaway3);
+
+    // Any colour will do, as long as it's a medium
+    let user_pref4: Preference = "*/M/*".parse().unwrap();
+    let giveaway4 = store.giveaway(&user_pref4);
+    report(&user_pref4, giveaway4);
+
+    println!("Left in stock:");
+    for (shirt, count) in store.stock_levels() {
+        println!("    {shirt}: {count}");
+    }
 
-    let user_pref2 = None;
-    let giveaway2 = store.giveaway(user_pref2);
+    // A crowd arriving at once, each user on a thread of their own, shares one
+    // stock and between them can never get more shirts than there are
+    let store = SharedInventory::new(store);
+    store.restock("green/L/polo".parse().unwrap(), 10).unwrap();
+    let crowd: Vec<_> = (0..CROWD)
+        .map(|_| {
+            let store = store.clone();
+            thread::spawn(move || store.giveaway(&Preference::any()).is_ok())
+        })
+        .collect();
+    let lucky = crowd
+        .into_iter()
+        .map(|user| user.join().unwrap())
+        .filter(|&got_one| got_one)
+        .count();
     println!(
-        "The user with preference {:?} gets {:?}", user_pref2, giveaway2
+        "{CROWD} users at once: {lucky} got a shirt, {} went without",
+        CROWD - lucky
     );
 
     let expensive_closure = |num: u32| -> u32 {
@@ -57,5 +90,37 @@ fn main() {
         num
     };
 
-    println!("{}", expensive_closure(5));
-}
\ No newline at end of file
+    // Only the first call waits; the cacher remembers the answer after that
+    let mut expensive_result = Cacher::new(expensive_closure);
+    println!("{}", expensive_result.value(5));
+    println!("{}", expensive_result.value(5));
+    println!(
+        "{} calculated, {} remembered",
+        expensive_result.misses(),
+        expensive_result.hits()
+    );
+
+    // Three at once on a pool of three threads take no longer than one, and
+    // the main thread is free in the meantime
+    let pool = Pool::new(3);
+    let mut tasks: Vec<_> = (1..=3)
+        .map(|num| pool.run(move |_| expensive_closure(num)))
+        .collect();
+    // A fourth has to queue for a free thread, and is cancelled before it gets one
+    let queued = pool.run(move |_| expensive_closure(4));
+    queued.cancel();
+
+    let finished_early = tasks[0].wait_timeout(Duration::from_millis(100));
+    let results: Vec<String> = tasks
+        .into_iter()
+        .map(|task| match task.wait() {
+            Ok(num) => num.to_string(),
+            Err(e) => e.to_string(),
+        })
+        .collect();
+    println!("Finished within 100ms: {finished_early}");
+    println!("{}", results.join(", "));
+    if let Err(e) = queued.wait() {
+        println!("The fourth: {e}");
+    }
+}
@@ -1,3 +1,5 @@
+use rusty_stuff::combinators::{compose, partial, pipe};
+
 fn main() {
     // These are all valid definitions that will produce the same behavior when they’re
     // called
@@ -37,4 +39,22 @@ fn main() {
     println!("Before calling closure: {list:?}");
     only_borrows();
     println!("After calling closure: {list:?}");
+
+    // Being equivalent, the four forms can stand in for each other when functions are
+    // built out of other functions, whether fn items or closures
+    let add_two = compose(add_one_v1, add_one_v2);
+    let add_four = pipe([
+        &add_one_v1 as &dyn Fn(u32) -> u32,
+        &add_one_v2,
+        &add_one_v3,
+        &add_one_v4,
+    ]);
+    let add = |a: u32, b: u32| a + b;
+    let add_one_v5 = partial(add, 1);
+    println!(
+        "Add two to 1: {}, add four to 1: {}, add one to 1: {}",
+        add_two(1),
+        add_four(1),
+        add_one_v5(1)
+    );
 }
\ No newline at end of file
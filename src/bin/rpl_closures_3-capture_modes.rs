@@ -0,0 +1,140 @@
+// The three ways a closure can capture a value, watched as they happen: the
+// book's list is a probe that logs what is done to it, and each closure is
+// handed to a function that only accepts Fn, FnMut or FnOnce, so the compiler
+// vouches for the trait and the log shows the capture. Each probe is settled
+// as soon as it is bound, so a closure that moves it is caught even if the
+// closure is the first to use it.
+use std::thread;
+
+use rusty_stuff::capture::{self, Event, Log};
+
+// What happens to the probes while `call` runs, leaving out anything before
+fn watch(log: &Log, call: impl FnOnce()) -> Vec<Event> {
+    log.take();
+    call();
+    log.take()
+}
+
+// Each of these drops the closure once it has been called, along with
+// anything it took by value
+fn call_fn(log: &Log, closure: impl Fn()) -> Vec<Event> {
+    watch(log, move || {
+        closure();
+        closure();
+    })
+}
+
+fn call_fn_mut(log: &Log, mut closure: impl FnMut()) -> Vec<Event> {
+    watch(log, move || {
+        closure();
+        closure();
+    })
+}
+
+fn call_fn_once(log: &Log, closure: impl FnOnce()) -> Vec<Event> {
+    watch(log, closure)
+}
+
+// https://doc.rust-lang.org/book/ch13-01-closures.html#capturing-references-or-moving-ownership
+fn only_borrows(log: &Log) -> Vec<Event> {
+    let list = log.probe("list", vec![1, 2, 3]);
+    list.settle();
+    println!("Before defining closure: {list:?}");
+
+    let only_borrows = || println!("From closure: {list:?}");
+
+    println!("Before calling closure: {list:?}");
+    let during = call_fn(log, only_borrows);
+    println!("After calling closure: {list:?}");
+    during
+}
+
+fn borrows_mutably(log: &Log) -> Vec<Event> {
+    let mut list = log.probe("list", vec![1, 2, 3]);
+    list.settle();
+    println!("Before defining closure: {list:?}");
+
+    let borrows_mutably = || list.get_mut().push(7);
+
+    let during = call_fn_mut(log, borrows_mutably);
+    println!("After calling closure: {list:?}");
+    during
+}
+
+// Only reads the list, so is Fn, but `move` makes it take the list anyway, and
+// the list goes when the closure does
+fn moves_into_a_thread(log: &Log) -> Vec<Event> {
+    let list = log.probe("list", vec![1, 2, 3]);
+    list.settle();
+    println!("Before defining closure: {list:?}");
+
+    watch(log, || {
+        thread::spawn(move || println!("From thread: {list:?}"))
+            .join()
+            .unwrap();
+    })
+}
+
+// Taking the list out of the probe uses it up, which the closure can only do
+// once, having taken the list by value without being told to
+fn uses_up(log: &Log) -> Vec<Event> {
+    let list = log.probe("list", vec![1, 2, 3]);
+    list.settle();
+    println!("Before defining closure: {list:?}");
+
+    let uses_up = || {
+        let list = list.into_inner();
+        println!("From closure: {} items used up", list.len());
+    };
+
+    call_fn_once(log, uses_up)
+}
+
+// A clone moved in leaves the original where it was
+fn moves_a_clone(log: &Log) -> Vec<Event> {
+    let list = log.probe("list", vec![1, 2, 3]);
+    list.settle();
+    let copy = list.clone();
+    copy.settle();
+    println!("Before defining closure: {copy:?}");
+
+    let reads_the_clone = move || println!("From closure: {copy:?}");
+
+    let during = call_fn(log, reads_the_clone);
+    println!("After calling closure: {list:?}");
+    during
+}
+
+fn show(heading: &str, events: &[Event]) {
+    if events.is_empty() {
+        return;
+    }
+    println!("{heading}:");
+    for event in events {
+        println!("    {event}");
+    }
+}
+
+fn run(log: &Log, name: &str, closure_trait: &str, demo: fn(&Log) -> Vec<Event>) {
+    println!("== {name} ({closure_trait}) ==");
+    let during = demo(log);
+
+    show("While the closure was called and dropped", &during);
+    for probe in ["list", "list'"] {
+        if let Some(capture) = capture::capture_of(&during, probe) {
+            println!("So {probe} was captured {capture}");
+        }
+    }
+    show("Afterwards", &log.take());
+    println!();
+}
+
+fn main() {
+    let log = Log::new();
+
+    run(&log, "only_borrows", "Fn", only_borrows);
+    run(&log, "borrows_mutably", "FnMut", borrows_mutably);
+    run(&log, "moves_into_a_thread", "Fn", moves_into_a_thread);
+    run(&log, "uses_up", "FnOnce", uses_up);
+    run(&log, "moves_a_clone", "Fn", moves_a_clone);
+}
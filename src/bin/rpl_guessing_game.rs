@@ -1,44 +1,210 @@
+use std::env;
 use std::io;
-use std::cmp::Ordering;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process;
+
 use rand::Rng;
+use rusty_stuff::guessing_game::multiplayer::Server;
+use rusty_stuff::guessing_game::scores::{self, HighScores, Score};
+use rusty_stuff::guessing_game::{self, Difficulty, Game, Settings, parse_attempts, parse_range};
 
 // Taken from https://doc.rust-lang.org/book/ch02-00-guessing-game-tutorial.html
+// and extended with difficulty levels and an optional limit on the number of
+// guesses, picked with flags or, without a difficulty flag, from a menu at the
+// start:
+//
+//     cargo run --bin rpl_guessing_game -- --hard --attempts 10
+//     cargo run --bin rpl_guessing_game -- --range 1-50
+//
+// The secret stays secret unless --debug is passed, which also shows the seed
+// the game was played with, so that --seed can play exactly the same game again.
+// Won games go in a high-score table per difficulty, unless they were debugged
+// or replayed with --seed, when the secret could already be known.
+//
+// With --serve, players connect over TCP instead, with netcat say, and race to
+// guess the same number:
+//
+//     cargo run --bin rpl_guessing_game -- --serve 127.0.0.1:7878
+//     nc 127.0.0.1 7878
 
 // Run rustup doc to open the local documentation in your browser.
 // cargo doc --open
-fn main() {
-    println!("Guess the number!");
 
-    let secret_number = rand::thread_rng().gen_range(1..=100);
+const USAGE: &str = "\
+Usage: rpl_guessing_game [--easy | --normal | --hard | --range <low>-<high>] [--attempts <n>]
+                         [--seed <n>] [--debug]
+       rpl_guessing_game --serve <address> [--easy | --normal | --hard | --range <low>-<high>]
+                         [--seed <n>]
+
+    --easy                   guess a number from 1 to 10
+    --normal                 guess a number from 1 to 100
+    --hard                   guess a number from 1 to 1000
+    --range <low>-<high>     guess a number from low to high
+    --attempts <n>           lose after n wrong guesses (default unlimited)
+    --seed <n>               pick the secret number from seed n, to play the same game again,
+                             leaving the game out of the high scores
+    --debug                  show the seed and the secret number before the first guess,
+                             leaving the game out of the high scores
+    --serve <address>        host a game for several players over TCP, such as on
+                             127.0.0.1:7878, with a new round after every win
 
-    println!("The secret number is: {secret_number}");
+Without a difficulty, the game asks for one before it starts, and for a limit
+on guesses unless --attempts is given, or is normal when serving. With a
+difficulty, the guesses are unlimited unless --attempts is given.";
 
-    loop {
-        println!("Enter your guess");
+// Every won game is appended here, so the high scores last between games
+const HIGH_SCORES: &str = "guessing_game_scores.csv";
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{message}\n\n{USAGE}");
+    process::exit(2);
+}
 
-        let mut guess = String::new();
+// What the flags chose. The difficulty and attempts may be left to the menu.
+#[derive(Debug, Default)]
+struct Options {
+    difficulty: Option<Difficulty>,
+    attempts: Option<u32>,
+    seed: Option<u64>,
+    debug: bool,
+    serve: Option<String>,
+}
 
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Failed to read line");
+fn parse_args(args: &[String]) -> Options {
+    let mut options = Options::default();
 
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => {
-                println!("Please enter a valid number!");
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let chosen = match arg.as_str() {
+            "--easy" => Difficulty::Easy,
+            "--normal" => Difficulty::Normal,
+            "--hard" => Difficulty::Hard,
+            "--range" => {
+                let value = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("--range needs a value"));
+                parse_range(value)
+                    .unwrap_or_else(|| usage_error(&format!("Not a range such as 1-50: {value}")))
+            }
+            "--attempts" => {
+                let value = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("--attempts needs a value"));
+                options.attempts =
+                    Some(parse_attempts(value).unwrap_or_else(|| {
+                        usage_error(&format!("Not a number of guesses: {value}"))
+                    }));
                 continue;
             }
+            "--seed" => {
+                let value = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("--seed needs a value"));
+                options.seed = Some(
+                    value
+                        .parse()
+                        .unwrap_or_else(|_| usage_error(&format!("Not a seed: {value}"))),
+                );
+                continue;
+            }
+            "--debug" => {
+                options.debug = true;
+                continue;
+            }
+            "--serve" => {
+                let value = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("--serve needs an address"));
+                options.serve = Some(value.clone());
+                continue;
+            }
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            _ => usage_error(&format!("Unknown option: {arg}")),
         };
 
-        println!("You guessed: {}", guess);
+        if options.difficulty.replace(chosen).is_some() {
+            usage_error("Pick one difficulty");
+        }
+    }
 
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                println!("You win!");
-                break
-            }
+    // Each player would need their own limit, and a debugged secret would be
+    // everyone's
+    if options.serve.is_some() && (options.attempts.is_some() || options.debug) {
+        usage_error("--serve can't be used with --attempts or --debug");
+    }
+
+    options
+}
+
+fn serve(address: &str, difficulty: Difficulty, seed: u64) {
+    let listener = TcpListener::bind(address).unwrap_or_else(|e| {
+        eprintln!("Can't listen on {address}: {e}");
+        process::exit(1);
+    });
+
+    let (low, high) = difficulty.range();
+    println!("Guessing numbers from {low} to {high} on {address}");
+    Server::new(difficulty, seed).serve(listener);
+}
+
+// Losing the high scores shouldn't spoil the game, so problems are only reported
+fn record_score(game: &Game) {
+    let path = Path::new(HIGH_SCORES);
+    let score = Score::of(game);
+
+    let result = HighScores::load(path).and_then(|mut high_scores| {
+        if high_scores.skipped() > 0 {
+            eprintln!(
+                "Skipped {} unreadable lines in {HIGH_SCORES}",
+                high_scores.skipped()
+            );
+        }
+        scores::save(path, &score)?;
+        high_scores.add(score.clone());
+        high_scores.write_table(&score, &mut io::stdout())
+    });
+
+    if let Err(e) = result {
+        eprintln!("Problem updating the high scores in {HIGH_SCORES}: {e}");
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = parse_args(&args);
+
+    // Always from a seed, so any game can be played again
+    let seed = options.seed.unwrap_or_else(|| rand::rng().random());
+
+    if let Some(address) = &options.serve {
+        serve(
+            address,
+            options.difficulty.unwrap_or(Difficulty::Normal),
+            seed,
+        );
+        return;
+    }
+
+    let settings = Settings {
+        difficulty: options.difficulty,
+        attempts: options.attempts,
+        seed,
+        debug: options.debug,
+    };
+
+    match guessing_game::run(settings, io::stdin().lock(), io::stdout()) {
+        // A debugged or replayed game may have been won knowing the secret
+        Ok(Some(game)) if game.is_won() && !settings.debug && options.seed.is_none() => {
+            record_score(&game)
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Problem talking to the terminal: {e}");
+            process::exit(1);
         }
     }
 }
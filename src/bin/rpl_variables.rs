@@ -26,5 +26,5 @@ fn main() {
     let spaces = spaces.len(); // type of spaces is now usize
 
     let mut spacez = "    ";
-    // spacez = spacez.len(); // this line would cause a compile-time error - type mismatch
+    // spacez = spacez.len(); // this line would cause a compile-time error - type mismatch [E0308]
 }
\ No newline at end of file
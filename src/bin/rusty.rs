@@ -0,0 +1,179 @@
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use rusty_stuff::sharing_and_ownership::{self, DEMOS, Demo};
+use rusty_stuff::variants::Topic;
+use rusty_stuff::{explain, playground, quiz};
+
+const USAGE: &str = "\
+Usage:
+    rusty list               list the ownership demos
+    rusty run <demo>...      run one or more demos by name
+    rusty run --all          run every demo in order
+    rusty run --trace ...    as run, printing when each String in the demo is
+                             created, moved, cloned, borrowed and dropped; only
+                             ownership_and_functions and return_values_and_scope
+                             can be traced
+    rusty play <demo>        uncomment a demo's lines and recompile interactively
+    rusty quiz [options]     does it compile, and if not, why not?
+    rusty explain [--markdown] [code...]
+                             rustc's explanation of each error code the demos
+                             cite, with the demos that cite it
+
+Quiz options:
+    --topic <topic>          only ask about moves, mutability, mutable-borrows,
+                             reborrows or scopes
+    --questions <n>          how many questions to ask (default 10)";
+
+// Scores from every quiz session are appended here, so progress can be compared
+const QUIZ_HISTORY: &str = "quiz_history.csv";
+
+fn list() {
+    let width = DEMOS.iter().map(|demo| demo.name.len()).max().unwrap_or(0);
+
+    for demo in DEMOS {
+        println!("{:width$}  [{}] {}", demo.name, demo.chapter, demo.summary);
+    }
+}
+
+// Only called with `trace` for demos that have a traced version
+fn run(demo: &Demo, trace: bool) {
+    println!("== {} ({}) ==", demo.name, demo.chapter);
+    match sharing_and_ownership::traced(demo.name) {
+        Some(traced) if trace => traced(),
+        _ => (demo.run)(),
+    }
+    println!();
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{message}\n\n{USAGE}");
+    process::exit(2);
+}
+
+fn quiz(options: &[String]) {
+    let mut topic = None;
+    let mut count = 10;
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let value = options
+            .next()
+            .unwrap_or_else(|| usage_error(&format!("{option} needs a value")));
+
+        match option.as_str() {
+            "--topic" => {
+                topic = Some(
+                    Topic::from_name(value)
+                        .unwrap_or_else(|| usage_error(&format!("Unknown topic: {value}"))),
+                )
+            }
+            "--questions" => {
+                count = value
+                    .parse()
+                    .unwrap_or_else(|_| usage_error(&format!("Not a number of questions: {value}")))
+            }
+            _ => usage_error(&format!("Unknown quiz option: {option}")),
+        }
+    }
+
+    let questions = quiz::questions(topic, count);
+    println!("Borrow checker quiz: {} questions", questions.len());
+
+    let history_path = Path::new(QUIZ_HISTORY);
+    let result = quiz::run(&questions, io::stdin().lock(), io::stdout()).and_then(|session| {
+        if !session.is_empty() {
+            quiz::save_session(history_path, &session)?;
+        }
+        let history = quiz::load_history(history_path)?;
+        quiz::print_summary(&session, &history, &mut io::stdout())
+    });
+
+    if let Err(e) = result {
+        eprintln!("Problem running the quiz: {e}");
+        process::exit(1);
+    }
+}
+
+fn explain(args: &[String]) {
+    let markdown = args.iter().any(|arg| arg == "--markdown");
+    let codes: Vec<String> = args
+        .iter()
+        .filter(|arg| *arg != "--markdown")
+        .map(|code| code.to_uppercase())
+        .collect();
+
+    let result = explain::entries(&codes).and_then(|entries| {
+        let mut out = io::stdout().lock();
+        if markdown {
+            explain::write_markdown(&entries, &mut out)
+        } else {
+            explain::write_terminal(&entries, &mut out)
+        }
+    });
+
+    if let Err(e) = result {
+        eprintln!("Problem writing the error code report: {e}");
+        process::exit(1);
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("list") => list(),
+        Some("run") => {
+            let trace = args[1..].iter().any(|arg| arg == "--trace");
+            let names: Vec<&String> = args[1..].iter().filter(|arg| *arg != "--trace").collect();
+
+            if names.is_empty() {
+                usage_error("Which demo? Pass a name from `rusty list` or --all");
+            }
+
+            // Check every name before running anything, so a typo doesn't leave
+            // half the output on screen
+            let demos: Vec<&Demo> = if names.iter().any(|name| *name == "--all") {
+                DEMOS.iter().collect()
+            } else {
+                names
+                    .iter()
+                    .map(|name| {
+                        sharing_and_ownership::find(name)
+                            .unwrap_or_else(|| usage_error(&format!("Unknown demo: {name}")))
+                    })
+                    .collect()
+            };
+
+            if trace
+                && let Some(demo) = demos
+                    .iter()
+                    .find(|demo| sharing_and_ownership::traced(demo.name).is_none())
+            {
+                usage_error(&format!("{} has no traced version", demo.name));
+            }
+
+            demos.into_iter().for_each(|demo| run(demo, trace));
+        }
+        Some("play") => {
+            let name = match &args[1..] {
+                [name] => name,
+                _ => usage_error("Which demo? Pass one name from `rusty list`"),
+            };
+            let demo = sharing_and_ownership::find(name)
+                .unwrap_or_else(|| usage_error(&format!("Unknown demo: {name}")));
+
+            if let Err(e) = playground::play(demo, io::stdin().lock(), io::stdout()) {
+                eprintln!("Problem talking to the terminal: {e}");
+                process::exit(1);
+            }
+        }
+        Some("quiz") => quiz(&args[1..]),
+        Some("explain") => explain(&args[1..]),
+        Some("help" | "--help" | "-h") => println!("{USAGE}"),
+        Some(other) => usage_error(&format!("Unknown command: {other}")),
+        None => usage_error("Missing command"),
+    }
+}
@@ -0,0 +1,310 @@
+// The free shirt giveaway from the closures chapter as a tool for the event
+// itself: the stock is kept in a file, so it picks up where it left off, and
+// everything that happens to it is recorded in a ledger.
+use std::env;
+use std::fmt;
+use std::io;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use rusty_stuff::inventory::ledger::{self, Entry, Event, Until};
+use rusty_stuff::inventory::report::{self, ReportFormat, Section};
+use rusty_stuff::inventory::server::Server;
+use rusty_stuff::inventory::shop::Shop;
+use rusty_stuff::inventory::store;
+use rusty_stuff::inventory::{Inventory, Preference, Shirt};
+
+const USAGE: &str = "\
+Usage:
+    shirts [--stock <file>] [--ledger <file>] <command>
+
+Commands:
+    stock                    show what is left
+    giveaway <user> [preference]
+                             give a shirt to someone who wants colour/size/style,
+                             with * for anything they don't mind, e.g. */M/*
+    restock <shirt> <count>  add shirts to the stock, e.g. restock red/M/crew 10
+    import <sheet>           replace the stock with a .json or .csv stock sheet
+    export <sheet>           write the stock to a .json or .csv stock sheet
+    replay [--entry <n> | --at <time>]
+                             rebuild the stock from the ledger as it was after
+                             entry n, or at a time in seconds since 1970
+    audit                    check the ledger for entries that don't add up
+    report [--csv | --json] [summary | colours | timeline]
+                             how many users got what they asked for, and how
+                             the stock ran down, from the ledger
+    serve [address]          answer giveaways over HTTP, on 127.0.0.1:7878
+                             unless another address is given
+
+The stock is kept in shirt_stock.json unless --stock names another .json or
+.csv file, and the ledger in shirt_ledger.jsonl unless --ledger says otherwise.";
+
+const DEFAULT_STOCK: &str = "shirt_stock.json";
+const DEFAULT_LEDGER: &str = "shirt_ledger.jsonl";
+// The port from the book's web server project
+const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{message}\n\n{USAGE}");
+    process::exit(2);
+}
+
+fn fail(message: &str, e: impl fmt::Display) -> ! {
+    eprintln!("{message}: {e}");
+    process::exit(1);
+}
+
+fn load(shop: &Shop) -> Inventory {
+    shop.load()
+        .unwrap_or_else(|e| fail("Can't read the stock", e))
+}
+
+fn entries(shop: &Shop) -> Vec<Entry> {
+    shop.entries()
+        .unwrap_or_else(|e| fail("Can't read the ledger", e))
+}
+
+fn record(shop: &Shop, opening: Option<Entry>, event: Event, inventory: &Inventory) {
+    if let Err(e) = shop.record(opening, event, inventory) {
+        fail("Can't record the change", e);
+    }
+}
+
+fn show(inventory: &Inventory) {
+    if inventory.stock_levels().next().is_none() {
+        println!("Nothing has been stocked yet");
+        return;
+    }
+
+    for (shirt, count) in inventory.stock_levels() {
+        println!("{count:>5}  {shirt}");
+    }
+    println!("{:>5}  in total", inventory.total());
+}
+
+fn giveaway(shop: &Shop, user: &str, preference: &[&str]) {
+    // The preference used to come first, with no user, so an old habit would
+    // otherwise give a shirt to someone called */M/*
+    if user.parse::<Preference>().is_ok() {
+        usage_error(&format!(
+            "{user:?} is a preference, not a user: giveaway <user> [preference]"
+        ));
+    }
+
+    let preference: Preference = match preference {
+        [] => Preference::any(),
+        [text] => text
+            .parse()
+            .unwrap_or_else(|e| usage_error(&format!("Bad preference {text:?}: {e}"))),
+        _ => usage_error("giveaway takes at most one preference"),
+    };
+
+    let mut inventory = load(shop);
+    let opening = shop.opening(&inventory);
+    match inventory.giveaway(&preference) {
+        Ok(shirt) => {
+            let event = Event::Giveaway {
+                user: user.to_string(),
+                preference,
+                shirt: shirt.clone(),
+                policy: inventory.policy_name().to_string(),
+            };
+            record(shop, opening, event, &inventory);
+            println!("Give {user} {shirt}");
+        }
+        Err(e) => {
+            let event = Event::Refused {
+                user: user.to_string(),
+                preference,
+            };
+            record(shop, opening, event, &inventory);
+            eprintln!("Sorry {user}, {e}");
+            process::exit(1);
+        }
+    }
+}
+
+fn replay(shop: &Shop, options: &[&str]) {
+    let entries = entries(shop);
+
+    let until = match options {
+        [] => Until::End,
+        ["--entry", n] => Until::Entry(
+            n.parse()
+                .unwrap_or_else(|_| usage_error(&format!("Not an entry number: {n}"))),
+        ),
+        ["--at", time] => Until::Time(
+            time.parse()
+                .unwrap_or_else(|_| usage_error(&format!("Not a time in seconds: {time}"))),
+        ),
+        _ => usage_error("replay takes either --entry <n> or --at <time>"),
+    };
+
+    let replayed = match until {
+        Until::Entry(n) if n > entries.len() => {
+            eprintln!("The ledger only has {} entries", entries.len());
+            process::exit(1);
+        }
+        Until::Entry(n) => {
+            println!("After entry {n} of {}:", entries.len());
+            ledger::replay(&entries, until)
+        }
+        Until::Time(time) => {
+            println!("At {time}:");
+            ledger::replay(&entries, until)
+        }
+        Until::End => {
+            println!("After all {} entries:", entries.len());
+            ledger::replay(&entries, until)
+        }
+    };
+    show(&replayed);
+}
+
+fn audit(shop: &Shop) {
+    let entries = entries(shop);
+    let mut problems: Vec<String> = ledger::audit(&entries)
+        .iter()
+        .map(|issue| issue.to_string())
+        .collect();
+
+    // The stock file should be where the ledger leaves off
+    if !entries.is_empty() {
+        let replayed = ledger::replay(&entries, Until::End);
+        let saved = load(shop);
+        let replayed: Vec<_> = replayed.stock_levels().collect();
+        let saved: Vec<_> = saved.stock_levels().collect();
+
+        if replayed != saved {
+            problems.push(format!(
+                "{} doesn't match the stock the ledger adds up to",
+                shop.stock.display()
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        println!("No problems found in {} entries", entries.len());
+        return;
+    }
+
+    for problem in &problems {
+        println!("{problem}");
+    }
+    process::exit(1);
+}
+
+fn report(shop: &Shop, options: &[&str]) {
+    let (format, sections) = match options {
+        ["--csv", sections @ ..] => (ReportFormat::Csv, sections),
+        ["--json", sections @ ..] => (ReportFormat::Json, sections),
+        sections => (ReportFormat::Table, sections),
+    };
+    let sections: Vec<Section> = match sections {
+        [] => Section::ALL.to_vec(),
+        [section] => vec![
+            section
+                .parse()
+                .unwrap_or_else(|e: String| usage_error(&format!("Bad report: {e}"))),
+        ],
+        _ => usage_error("report takes at most one section"),
+    };
+
+    let report = report::report(&entries(shop));
+    if let Err(e) = report.write(format, &sections, io::stdout().lock()) {
+        eprintln!("Can't write the report: {e}");
+        process::exit(1);
+    }
+}
+
+fn serve(shop: Shop, address: &[&str]) {
+    let address = match address {
+        [] => DEFAULT_ADDRESS,
+        [address] => address,
+        _ => usage_error("serve takes at most one address"),
+    };
+
+    let server = Server::keeping(shop).unwrap_or_else(|e| fail("Can't read the stock", e));
+    let listener = TcpListener::bind(address).unwrap_or_else(|e| {
+        eprintln!("Can't listen on {address}: {e}");
+        process::exit(1);
+    });
+
+    println!("Giving away shirts on http://{address}");
+    server.serve(listener);
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    let mut stock = PathBuf::from(DEFAULT_STOCK);
+    let mut ledger = PathBuf::from(DEFAULT_LEDGER);
+    while let Some(option @ ("--stock" | "--ledger")) = args.first().map(String::as_str) {
+        if args.len() < 2 {
+            usage_error(&format!("{option} needs a file name"));
+        }
+        let path = PathBuf::from(args.remove(1));
+        if args.remove(0) == "--stock" {
+            stock = path;
+        } else {
+            ledger = path;
+        }
+    }
+    let shop = Shop::new(stock, ledger);
+
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["stock"] => show(&load(&shop)),
+        ["giveaway", user, preference @ ..] => giveaway(&shop, user, preference),
+        ["restock", shirt, count] => {
+            let shirt: Shirt = shirt
+                .parse()
+                .unwrap_or_else(|e| usage_error(&format!("Bad shirt {shirt:?}: {e}")));
+            let count: u32 = count
+                .parse()
+                .unwrap_or_else(|_| usage_error(&format!("Not a number of shirts: {count}")));
+
+            let mut inventory = load(&shop);
+            let opening = shop.opening(&inventory);
+            inventory
+                .restock(shirt.clone(), count)
+                .unwrap_or_else(|e| fail("Can't restock", e));
+            record(
+                &shop,
+                opening,
+                Event::Restock {
+                    shirt: shirt.clone(),
+                    count,
+                },
+                &inventory,
+            );
+            println!("{} {shirt} now in stock", inventory.stock(&shirt));
+        }
+        ["import", sheet] => {
+            let sheet = Path::new(sheet);
+            let imported = store::load(sheet)
+                .unwrap_or_else(|e| fail(&format!("Can't import {}", sheet.display()), e));
+            let opening = shop.opening(&load(&shop));
+            record(&shop, opening, Event::stocktake(&imported), &imported);
+            show(&imported);
+        }
+        ["export", sheet] => {
+            let sheet = Path::new(sheet);
+            if let Err(e) = store::save(&load(&shop), sheet) {
+                fail(&format!("Can't export to {}", sheet.display()), e);
+            }
+        }
+        ["serve", address @ ..] => serve(shop, address),
+        ["replay", options @ ..] => replay(&shop, options),
+        ["audit"] => audit(&shop),
+        ["report", options @ ..] => report(&shop, options),
+        ["help" | "--help" | "-h"] => println!("{USAGE}"),
+        [] => usage_error("Missing command"),
+        [command, ..] => usage_error(&format!("Unknown command or wrong arguments: {command}")),
+    }
+}
@@ -0,0 +1,159 @@
+// The `Cacher` the closures chapter used to build for its expensive closure
+// (https://doc.rust-lang.org/1.30.0/book/second-edition/ch13-01-closures.html),
+// grown up: it remembers a value per argument rather than just the first, only
+// keeps as many as it is told to, dropping whichever was used least recently,
+// and can forget values once they are too old to trust.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+struct Slot<V> {
+    value: V,
+    stored: Instant,
+    // When it was last looked up, on the cacher's own clock
+    used: u64,
+}
+
+pub struct Cacher<F, K, V>
+where
+    F: Fn(K) -> V,
+{
+    calculation: F,
+    values: HashMap<K, Slot<V>>,
+    // The key of every slot by when it was last used, least recent first
+    recency: BTreeMap<u64, K>,
+    // Ticks once per lookup
+    clock: u64,
+    capacity: usize,
+    time_to_live: Option<Duration>,
+    hits: u64,
+    misses: u64,
+}
+
+impl<F, K, V> Cacher<F, K, V>
+where
+    F: Fn(K) -> V,
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    // Remembers every value it calculates, for ever
+    pub fn new(calculation: F) -> Cacher<F, K, V> {
+        Cacher {
+            calculation,
+            values: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            capacity: usize::MAX,
+            time_to_live: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    // Keeps at most this many values, making room by dropping the one used
+    // least recently. A capacity of zero keeps nothing.
+    pub fn capacity(mut self, capacity: usize) -> Cacher<F, K, V> {
+        self.capacity = capacity;
+        self
+    }
+
+    // Calculates values again once they are this old
+    pub fn time_to_live(mut self, time_to_live: Duration) -> Cacher<F, K, V> {
+        self.time_to_live = Some(time_to_live);
+        self
+    }
+
+    pub fn value(&mut self, arg: K) -> V {
+        self.clock += 1;
+        let now = Instant::now();
+        let fresh = |slot: &Slot<V>| {
+            self.time_to_live
+                .is_none_or(|ttl| now.duration_since(slot.stored) < ttl)
+        };
+
+        match self.values.get_mut(&arg) {
+            Some(slot) if fresh(slot) => {
+                self.hits += 1;
+                let key = self
+                    .recency
+                    .remove(&slot.used)
+                    .expect("every slot is in recency");
+                slot.used = self.clock;
+                self.recency.insert(slot.used, key);
+                return slot.value.clone();
+            }
+            Some(_) => self.forget(&arg),
+            None => {}
+        }
+
+        self.misses += 1;
+        let value = (self.calculation)(arg.clone());
+        if self.capacity == 0 {
+            return value;
+        }
+
+        while self.values.len() >= self.capacity {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.values.remove(&oldest);
+        }
+        self.recency.insert(self.clock, arg.clone());
+        self.values.insert(
+            arg,
+            Slot {
+                value: value.clone(),
+                stored: now,
+                used: self.clock,
+            },
+        );
+        value
+    }
+
+    fn forget(&mut self, arg: &K) {
+        if let Some(slot) = self.values.remove(arg) {
+            self.recency.remove(&slot.used);
+        }
+    }
+
+    // How many values are remembered, including any that have gone stale but
+    // not been looked up since
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // Lookups answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    // Lookups that had to run the calculation
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.recency.clear();
+    }
+}
+
+impl<F, K, V> fmt::Debug for Cacher<F, K, V>
+where
+    F: Fn(K) -> V,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cacher")
+            .field("len", &self.values.len())
+            .field("capacity", &self.capacity)
+            .field("time_to_live", &self.time_to_live)
+            .field("hits", &self.hits)
+            .field("misses", &self.misses)
+            .finish_non_exhaustive()
+    }
+}
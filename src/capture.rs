@@ -0,0 +1,225 @@
+// Values that say what happens to them, for watching how closures capture
+// (https://doc.rust-lang.org/book/ch13-01-closures.html#capturing-references-or-moving-ownership).
+// A `Probe` logs each time its value is borrowed, mutably borrowed, cloned,
+// taken out or dropped. Rust can't watch a plain move, but a probe remembers
+// where it was settled, or else first used, so a later use from somewhere
+// else, such as from inside a closure that took it, is logged as moved. A
+// probe returned from `Log::probe` can't know where it will live, so call
+// `settle` once it is bound; otherwise a first use inside a closure can't tell
+// whether the closure took it.
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+// `moved` is None for the first use of a probe that was never settled, which
+// has nowhere to compare with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Borrowed { name: String, moved: Option<bool> },
+    BorrowedMut { name: String, moved: Option<bool> },
+    // Into a new probe, named after this one with a ' on the end
+    Cloned { name: String },
+    // The value was taken out with `into_inner`, using the probe up
+    Consumed { name: String },
+    Dropped { name: String },
+}
+
+fn place(moved: Option<bool>) -> &'static str {
+    match moved {
+        Some(false) => "in place",
+        Some(true) => "after a move",
+        None => "before it was settled, so maybe after a move",
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Borrowed { name, moved } => write!(f, "{name} borrowed {}", place(*moved)),
+            Event::BorrowedMut { name, moved } => {
+                write!(f, "{name} mutably borrowed {}", place(*moved))
+            }
+            Event::Cloned { name } => write!(f, "{name} cloned"),
+            Event::Consumed { name } => write!(f, "{name} used up"),
+            Event::Dropped { name } => write!(f, "{name} dropped"),
+        }
+    }
+}
+
+// Where the probes write. Clones share the same log, and it can be shared
+// between threads.
+#[derive(Debug, Clone, Default)]
+pub struct Log {
+    events: Arc<Mutex<Vec<Event>>>,
+}
+
+impl Log {
+    pub fn new() -> Log {
+        Log::default()
+    }
+
+    pub fn probe<T>(&self, name: &str, value: T) -> Probe<T> {
+        Probe {
+            name: name.to_string(),
+            value: Some(value),
+            log: self.clone(),
+            home: AtomicUsize::new(0),
+        }
+    }
+
+    fn push(&self, event: Event) {
+        self.events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(event);
+    }
+
+    // Everything logged since the last time, oldest first
+    pub fn take(&self) -> Vec<Event> {
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        events.drain(..).collect()
+    }
+}
+
+// From the weakest capture to the strongest, then one that can't be told
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Capture {
+    ByReference,
+    ByMutableReference,
+    ByValue,
+    // Borrowed before the probe was settled, so it could have been either a
+    // borrow or a move
+    Unknown,
+}
+
+impl fmt::Display for Capture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Capture::ByReference => write!(f, "by reference"),
+            Capture::ByMutableReference => write!(f, "by mutable reference"),
+            Capture::ByValue => write!(f, "by value"),
+            Capture::Unknown => write!(f, "somehow, as it was never settled"),
+        }
+    }
+}
+
+// How a closure must have captured the probe called `name`, going by what the
+// closure did to it: anything used after a move or used up was taken by
+// value, otherwise the strongest borrow decides, unless a borrow can't say
+// whether the probe had moved. None if it was never used.
+pub fn capture_of(events: &[Event], name: &str) -> Option<Capture> {
+    let mut capture = None;
+    for event in events {
+        let seen = match event {
+            Event::Borrowed { name: n, moved } if n == name => match moved {
+                Some(true) => Capture::ByValue,
+                Some(false) => Capture::ByReference,
+                None => Capture::Unknown,
+            },
+            Event::BorrowedMut { name: n, moved } if n == name => match moved {
+                Some(true) => Capture::ByValue,
+                Some(false) => Capture::ByMutableReference,
+                None => Capture::Unknown,
+            },
+            Event::Consumed { name: n } if n == name => Capture::ByValue,
+            _ => continue,
+        };
+        // Taken by value is certain, and an unknown beats any borrow
+        capture = Some(match (capture, seen) {
+            (Some(Capture::ByValue), _) | (_, Capture::ByValue) => Capture::ByValue,
+            (Some(Capture::Unknown), _) | (_, Capture::Unknown) => Capture::Unknown,
+            (Some(Capture::ByMutableReference), _) => Capture::ByMutableReference,
+            (_, seen) => seen,
+        });
+    }
+    capture
+}
+
+pub struct Probe<T> {
+    name: String,
+    // Only None once the value has been taken out
+    value: Option<T>,
+    log: Log,
+    // The address of the probe when it was settled or first used, or 0
+    // before then
+    home: AtomicUsize,
+}
+
+impl<T> Probe<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Remembers where the probe is now, so that a use from anywhere else is
+    // known to be after a move. Only the first settle or use counts.
+    pub fn settle(&self) {
+        self.moved();
+    }
+
+    // Whether the probe has left the place it was settled, or None if it
+    // hadn't been settled, in which case it is now
+    fn moved(&self) -> Option<bool> {
+        let here = self as *const Probe<T> as usize;
+        match self
+            .home
+            .compare_exchange(0, here, Ordering::SeqCst, Ordering::SeqCst)
+        {
+            Ok(_) => None,
+            Err(home) => Some(home != here),
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        self.log.push(Event::Borrowed {
+            name: self.name.clone(),
+            moved: self.moved(),
+        });
+        self.value
+            .as_ref()
+            .expect("only into_inner takes the value")
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.log.push(Event::BorrowedMut {
+            name: self.name.clone(),
+            moved: self.moved(),
+        });
+        self.value
+            .as_mut()
+            .expect("only into_inner takes the value")
+    }
+
+    pub fn into_inner(mut self) -> T {
+        self.log.push(Event::Consumed {
+            name: self.name.clone(),
+        });
+        self.value.take().expect("only into_inner takes the value")
+    }
+}
+
+impl<T: Clone> Clone for Probe<T> {
+    fn clone(&self) -> Probe<T> {
+        self.log.push(Event::Cloned {
+            name: self.name.clone(),
+        });
+        let value = self.value.clone().expect("only into_inner takes the value");
+        self.log.probe(&format!("{}'", self.name), value)
+    }
+}
+
+// Printing a probe borrows it, so is logged like `get`
+impl<T: fmt::Debug> fmt::Debug for Probe<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl<T> Drop for Probe<T> {
+    fn drop(&mut self) {
+        if self.value.is_some() {
+            self.log.push(Event::Dropped {
+                name: self.name.clone(),
+            });
+        }
+    }
+}
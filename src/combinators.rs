@@ -0,0 +1,72 @@
+// Building new functions out of old ones. Everything here takes anything that
+// can be called, so `fn` items and closures mix freely, as with the four forms
+// of add_one in the closures chapter:
+// https://doc.rust-lang.org/book/ch13-01-closures.html#closure-type-inference-and-annotation
+use std::rc::Rc;
+
+// f after g, as in maths: compose(f, g)(x) is f(g(x))
+pub fn compose<A, B, C>(f: impl Fn(B) -> C, g: impl Fn(A) -> B) -> impl Fn(A) -> C {
+    move |x| f(g(x))
+}
+
+// f and then g, in the order they are read: and_then(f, g)(x) is g(f(x))
+pub fn and_then<A, B, C>(f: impl Fn(A) -> B, g: impl Fn(B) -> C) -> impl Fn(A) -> C {
+    move |x| g(f(x))
+}
+
+// Each step in turn, first to last. The steps all take and give the same
+// type, so closures of different types can be passed as `&dyn Fn`. No steps
+// at all gives back what it is given.
+pub fn pipe<T, F: Fn(T) -> T>(steps: impl IntoIterator<Item = F>) -> impl Fn(T) -> T {
+    let steps: Vec<F> = steps.into_iter().collect();
+    move |x| steps.iter().fold(x, |x, step| step(x))
+}
+
+// A function of two arguments as a function of the first, which gives a
+// function of the second: curry(f)(a)(b) is f(a, b). That second function is
+// boxed, as `impl Fn` can't yet be what an `impl Fn` returns.
+pub fn curry<'a, A, B, C>(f: impl Fn(A, B) -> C + 'a) -> impl Fn(A) -> Box<dyn Fn(B) -> C + 'a>
+where
+    A: Clone + 'a,
+    B: 'a,
+    C: 'a,
+{
+    let f = Rc::new(f);
+    move |a| {
+        let f = Rc::clone(&f);
+        Box::new(move |b| f(a.clone(), b))
+    }
+}
+
+// The first argument filled in: partial(f, a)(b) is f(a, b)
+pub fn partial<A: Clone, B, C>(f: impl Fn(A, B) -> C, a: A) -> impl Fn(B) -> C {
+    move |b| f(a.clone(), b)
+}
+
+// Passes its argument straight through, after showing it to `look`. Handy in
+// the middle of a chain for printing or counting.
+pub fn tap<T>(look: impl Fn(&T)) -> impl Fn(T) -> T {
+    move |x| {
+        look(&x);
+        x
+    }
+}
+
+// Calls f again while it fails, up to `attempts` times in all, giving the
+// first success or the last failure. f is always called at least once, so
+// `attempts` of 0 behaves the same as 1.
+pub fn retry<A: Clone, B, E>(
+    f: impl Fn(A) -> Result<B, E>,
+    attempts: u32,
+) -> impl Fn(A) -> Result<B, E> {
+    move |x| {
+        let mut result = f(x.clone());
+        for _ in 1..attempts {
+            if result.is_ok() {
+                break;
+            }
+            result = f(x.clone());
+        }
+        result
+    }
+}
@@ -0,0 +1,137 @@
+// Runs the locally installed rustc over a standalone snippet, the same way
+// the compiler would see it if the line were uncommented in the demo itself.
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Matches the edition in Cargo.toml, so the snippets get the same borrow checker
+const EDITION: &str = "2024";
+
+static SCRATCH_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub struct Compilation {
+    pub success: bool,
+    pub stderr: String,
+}
+
+impl Compilation {
+    // The codes from lines such as "error[E0502]: cannot borrow `s` as mutable ...",
+    // in the order rustc reported them
+    pub fn error_codes(&self) -> Vec<String> {
+        let mut codes: Vec<String> = Vec::new();
+
+        for line in self.stderr.lines() {
+            let Some(rest) = line.strip_prefix("error[") else {
+                continue;
+            };
+            let Some(end) = rest.find(']') else {
+                continue;
+            };
+            let code = &rest[..end];
+
+            if !codes.iter().any(|seen| seen == code) {
+                codes.push(code.to_string());
+            }
+        }
+
+        codes
+    }
+}
+
+// A directory of its own for each compilation, so tests can run in parallel
+struct Scratch {
+    dir: PathBuf,
+}
+
+impl Scratch {
+    fn new() -> io::Result<Scratch> {
+        let dir = env::temp_dir().join(format!(
+            "rusty-stuff-{}-{}",
+            std::process::id(),
+            SCRATCH_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir)?;
+        Ok(Scratch { dir })
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn rustc() -> Command {
+    // Cargo sets RUSTC for build scripts; honour it if someone points us elsewhere
+    Command::new(env::var("RUSTC").unwrap_or_else(|_| String::from("rustc")))
+}
+
+fn compile(scratch: &Scratch, source: &str, args: &[&str]) -> io::Result<Compilation> {
+    let file = scratch.dir.join("snippet.rs");
+    fs::write(&file, source)?;
+
+    let output = rustc()
+        .args(["--edition", EDITION, "--crate-type", "bin"])
+        .args(args)
+        .arg("--out-dir")
+        .arg(&scratch.dir)
+        .arg(&file)
+        .output()?;
+
+    Ok(Compilation {
+        success: output.status.success(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+// Type and borrow checks the snippet without producing a binary
+pub fn check(source: &str) -> io::Result<Compilation> {
+    let scratch = Scratch::new()?;
+    compile(&scratch, source, &["--emit=metadata"])
+}
+
+pub struct Run {
+    pub compilation: Compilation,
+    // None when the snippet did not compile
+    pub output: Option<Output>,
+}
+
+// Builds the snippet and, if that worked, runs it. Warnings are left out, since
+// the demos are full of deliberately unused bindings.
+pub fn run(source: &str) -> io::Result<Run> {
+    let scratch = Scratch::new()?;
+    let compilation = compile(&scratch, source, &["-A", "warnings"])?;
+
+    let output = if compilation.success {
+        Some(
+            Command::new(
+                scratch
+                    .dir
+                    .join(format!("snippet{}", env::consts::EXE_SUFFIX)),
+            )
+            .output()?,
+        )
+    } else {
+        None
+    };
+
+    Ok(Run {
+        compilation,
+        output,
+    })
+}
+
+// The long form explanation rustc ships for an error code, as Markdown.
+// None if this rustc doesn't know the code.
+pub fn explain(code: &str) -> io::Result<Option<String>> {
+    let output = rustc().args(["--explain", code]).output()?;
+
+    if output.status.success() {
+        Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+    } else {
+        Ok(None)
+    }
+}
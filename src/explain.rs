@@ -0,0 +1,228 @@
+// Cross references the error codes cited in the demo comments, such as
+// "Value used after being moved [E0382]", with rustc's own explanation of them.
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::compiler;
+
+// Every file with demo code in it, as it would be opened from the repository root
+const SOURCES: &[(&str, &str)] = &[
+    (
+        "src/sharing_and_ownership.rs",
+        include_str!("sharing_and_ownership.rs"),
+    ),
+    (
+        "src/bin/rpl_variables.rs",
+        include_str!("bin/rpl_variables.rs"),
+    ),
+    (
+        "src/bin/rpl_closures_1-free_t_shirt.rs",
+        include_str!("bin/rpl_closures_1-free_t_shirt.rs"),
+    ),
+    (
+        "src/bin/rpl_closures_2.rs",
+        include_str!("bin/rpl_closures_2.rs"),
+    ),
+    (
+        "src/bin/rpl_error_handling_1.rs",
+        include_str!("bin/rpl_error_handling_1.rs"),
+    ),
+    (
+        "src/bin/rpl_error_handling_2.rs",
+        include_str!("bin/rpl_error_handling_2.rs"),
+    ),
+    (
+        "src/bin/rpl_guessing_game.rs",
+        include_str!("bin/rpl_guessing_game.rs"),
+    ),
+    ("src/bin/rpl_panic.rs", include_str!("bin/rpl_panic.rs")),
+];
+
+const ERROR_INDEX: &str = "https://doc.rust-lang.org/error_codes";
+
+pub struct Citation {
+    pub file: &'static str,
+    // One based, as editors and rustc count them
+    pub line: usize,
+    // The top level function the comment sits in, qualified with the
+    // binary's name for the files under src/bin
+    pub function: String,
+    pub comment: String,
+}
+
+pub struct Entry {
+    pub code: String,
+    // None if the local rustc has no explanation for the code
+    pub explanation: Option<String>,
+    pub citations: Vec<Citation>,
+}
+
+// The codes written as [E0123] on a line
+pub fn cited_codes(line: &str) -> Vec<String> {
+    line.match_indices("[E")
+        .filter_map(|(start, _)| {
+            let code = line.get(start + 1..start + 6)?;
+            let digits = code[1..].chars().all(|c| c.is_ascii_digit());
+            let closed = line[start + 6..].starts_with(']');
+            (digits && closed).then(|| code.to_string())
+        })
+        .collect()
+}
+
+fn top_level_function(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("fn ")?;
+    rest.split(['(', '<']).next()
+}
+
+// Every cited code, with where it was cited
+pub fn citations() -> BTreeMap<String, Vec<Citation>> {
+    let mut citations: BTreeMap<String, Vec<Citation>> = BTreeMap::new();
+
+    for (file, source) in SOURCES {
+        let mut function = String::new();
+
+        for (i, line) in source.lines().enumerate() {
+            if let Some(name) = top_level_function(line) {
+                // Every binary has a main, so say which one
+                function = match file.strip_prefix("src/bin/") {
+                    Some(binary) => format!("{}::{name}", binary.trim_end_matches(".rs")),
+                    None => name.to_string(),
+                };
+            }
+
+            for code in cited_codes(line) {
+                let comment = line.trim().trim_start_matches('/').trim().to_string();
+
+                citations.entry(code).or_default().push(Citation {
+                    file,
+                    line: i + 1,
+                    function: function.clone(),
+                    comment,
+                });
+            }
+        }
+    }
+
+    citations
+}
+
+// The cited codes with their explanations, limited to `codes` unless it is empty
+pub fn entries(codes: &[String]) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    for (code, citations) in citations() {
+        if !codes.is_empty() && !codes.contains(&code) {
+            continue;
+        }
+
+        let explanation = compiler::explain(&code)?;
+        entries.push(Entry {
+            code,
+            explanation,
+            citations,
+        });
+    }
+
+    Ok(entries)
+}
+
+pub fn write_terminal(entries: &[Entry], out: &mut impl Write) -> io::Result<()> {
+    for entry in entries {
+        writeln!(out, "{}  {ERROR_INDEX}/{}.html", entry.code, entry.code)?;
+        writeln!(out, "\n  Demonstrated by:")?;
+
+        for citation in &entry.citations {
+            let location = format!("{}:{}", citation.file, citation.line);
+            writeln!(out, "    {:<58} {location}", citation.function)?;
+            writeln!(out, "      // {}", citation.comment)?;
+        }
+
+        writeln!(out)?;
+        match &entry.explanation {
+            Some(explanation) => {
+                for line in explanation.lines() {
+                    writeln!(out, "  {line}")?;
+                }
+            }
+            None => writeln!(out, "  rustc has no explanation for {}", entry.code)?,
+        }
+        writeln!(out, "\n{}\n", "-".repeat(80))?;
+    }
+
+    Ok(())
+}
+
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+pub fn write_markdown(entries: &[Entry], out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "# Error codes cited in the demos\n")?;
+    writeln!(out, "| Code | Demos |")?;
+    writeln!(out, "|------|-------|")?;
+
+    for entry in entries {
+        let mut functions: Vec<String> = entry
+            .citations
+            .iter()
+            .map(|citation| format!("`{}`", citation.function))
+            .collect();
+        functions.dedup();
+
+        let code = entry.code.to_lowercase();
+        writeln!(
+            out,
+            "| [{}](#{code}) | {} |",
+            entry.code,
+            functions.join(", ")
+        )?;
+    }
+
+    for entry in entries {
+        writeln!(out, "\n## {}\n", entry.code)?;
+        writeln!(
+            out,
+            "[{} in the error index]({ERROR_INDEX}/{}.html)\n",
+            entry.code, entry.code
+        )?;
+        writeln!(out, "| Demo | Line | Comment |")?;
+        writeln!(out, "|------|------|---------|")?;
+
+        for citation in &entry.citations {
+            writeln!(
+                out,
+                "| `{}` | [{}:{}]({}#L{}) | {} |",
+                citation.function,
+                citation.file,
+                citation.line,
+                citation.file,
+                citation.line,
+                table_cell(&citation.comment)
+            )?;
+        }
+
+        writeln!(out)?;
+        match &entry.explanation {
+            // rustc's explanations use top level headings of their own, which
+            // would otherwise sit level with the title of this report
+            Some(explanation) => {
+                let mut in_code = false;
+
+                for line in explanation.lines() {
+                    if line.starts_with("```") {
+                        in_code = !in_code;
+                    }
+
+                    if line.starts_with('#') && !in_code {
+                        writeln!(out, "##{line}")?;
+                    } else {
+                        writeln!(out, "{line}")?;
+                    }
+                }
+            }
+            None => writeln!(out, "rustc has no explanation for {}.", entry.code)?,
+        }
+    }
+
+    Ok(())
+}
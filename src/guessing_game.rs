@@ -0,0 +1,341 @@
+// The guessing game from chapter 2
+// (https://doc.rust-lang.org/book/ch02-00-guessing-game-tutorial.html), taken
+// out of `main` so it can be tested. A `Game` judges one guess at a time, and
+// `run` plays a whole game over any input and output, asking for whatever the
+// settings leave open first. Won games can be kept in the `scores` table, and
+// `multiplayer` lets several players race for the same secret over TCP.
+use std::cmp::Ordering;
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+pub mod multiplayer;
+pub mod scores;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Custom { low: u32, high: u32 },
+}
+
+impl Difficulty {
+    // The lowest and highest the secret can be
+    pub fn range(self) -> (u32, u32) {
+        match self {
+            Difficulty::Easy => (1, 10),
+            Difficulty::Normal => (1, 100),
+            Difficulty::Hard => (1, 1000),
+            Difficulty::Custom { low, high } => (low, high),
+        }
+    }
+
+    // As the menu names it, or the range for a custom game
+    pub fn name(self) -> String {
+        match self {
+            Difficulty::Easy => String::from("easy"),
+            Difficulty::Normal => String::from("normal"),
+            Difficulty::Hard => String::from("hard"),
+            Difficulty::Custom { low, high } => format!("{low}-{high}"),
+        }
+    }
+}
+
+// A custom range, written as low-high
+pub fn parse_range(text: &str) -> Option<Difficulty> {
+    let (low, high) = text.split_once('-')?;
+    let low = low.trim().parse().ok()?;
+    let high = high.trim().parse().ok()?;
+
+    if low <= high {
+        Some(Difficulty::Custom { low, high })
+    } else {
+        None
+    }
+}
+
+// Zero guesses would lose before the game began
+pub fn parse_attempts(text: &str) -> Option<u32> {
+    text.trim().parse().ok().filter(|&attempts| attempts > 0)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    // Not a number, so it doesn't count as a guess
+    Invalid,
+    TooSmall,
+    TooBig,
+    Win,
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    difficulty: Difficulty,
+    secret: u32,
+    attempts: Option<u32>,
+    guessed: u32,
+    won: bool,
+    started: Instant,
+    // How long the game took, once it is over
+    took: Option<Duration>,
+}
+
+impl Game {
+    // The same seed always picks the same secret, on any platform and with any
+    // version of rand, as ChaCha8Rng promises to give the same numbers where
+    // StdRng doesn't
+    pub fn new(difficulty: Difficulty, attempts: Option<u32>, seed: u64) -> Game {
+        let (low, high) = difficulty.range();
+        let secret = ChaCha8Rng::seed_from_u64(seed).random_range(low..=high);
+        Game::with_secret(difficulty, attempts, secret)
+    }
+
+    // A game whose secret is already known, such as for a test
+    pub fn with_secret(difficulty: Difficulty, attempts: Option<u32>, secret: u32) -> Game {
+        Game {
+            difficulty,
+            secret,
+            attempts,
+            guessed: 0,
+            won: false,
+            started: Instant::now(),
+            took: None,
+        }
+    }
+
+    pub fn secret(&self) -> u32 {
+        self.secret
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn range(&self) -> (u32, u32) {
+        self.difficulty.range()
+    }
+
+    pub fn attempts(&self) -> Option<u32> {
+        self.attempts
+    }
+
+    // Valid guesses so far, including the winning one
+    pub fn guessed(&self) -> u32 {
+        self.guessed
+    }
+
+    // Guesses left before the player loses, if there is a limit
+    pub fn remaining(&self) -> Option<u32> {
+        self.attempts
+            .map(|attempts| attempts.saturating_sub(self.guessed))
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.won
+    }
+
+    pub fn is_lost(&self) -> bool {
+        !self.won && self.remaining() == Some(0)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_won() || self.is_lost()
+    }
+
+    // From when the game was made until it was won or lost, or until now if
+    // it is still going
+    pub fn elapsed(&self) -> Duration {
+        self.took.unwrap_or_else(|| self.started.elapsed())
+    }
+
+    // Panics if the game is already over, as there is nothing left to guess
+    pub fn guess(&mut self, guess: &str) -> Outcome {
+        assert!(!self.is_over(), "the game is over");
+
+        let guess: u32 = match guess.trim().parse() {
+            Ok(num) => num,
+            Err(_) => return Outcome::Invalid,
+        };
+
+        self.guessed += 1;
+        let outcome = match guess.cmp(&self.secret) {
+            Ordering::Less => Outcome::TooSmall,
+            Ordering::Greater => Outcome::TooBig,
+            Ordering::Equal => {
+                self.won = true;
+                Outcome::Win
+            }
+        };
+
+        if self.is_over() {
+            self.took = Some(self.started.elapsed());
+        }
+        outcome
+    }
+}
+
+// How to play a game with `run`. Without a difficulty the menu asks for one at
+// the start, and for the attempts too if they are None. Given a difficulty,
+// the menu is skipped, and attempts of None mean unlimited guesses.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub difficulty: Option<Difficulty>,
+    pub attempts: Option<u32>,
+    pub seed: u64,
+    // Shows the seed and the secret before the first guess
+    pub debug: bool,
+}
+
+fn guesses(count: u32) -> String {
+    if count == 1 {
+        String::from("1 guess")
+    } else {
+        format!("{count} guesses")
+    }
+}
+
+// Asks until it gets an answer `parse` accepts, or None if the input runs out
+fn ask<B: BufRead, T>(
+    question: &str,
+    parse: impl Fn(&str) -> Option<T>,
+    input: &mut io::Lines<B>,
+    out: &mut impl Write,
+) -> io::Result<Option<T>> {
+    loop {
+        writeln!(out, "{question}")?;
+        let Some(line) = input.next() else {
+            return Ok(None);
+        };
+        match parse(&line?) {
+            Some(answer) => return Ok(Some(answer)),
+            None => writeln!(out, "Sorry, I didn't understand that.")?,
+        }
+    }
+}
+
+fn choose_difficulty<B: BufRead>(
+    input: &mut io::Lines<B>,
+    out: &mut impl Write,
+) -> io::Result<Option<Difficulty>> {
+    writeln!(out, "Choose a difficulty:")?;
+    writeln!(out, "  1) easy     1 to 10")?;
+    writeln!(out, "  2) normal   1 to 100")?;
+    writeln!(out, "  3) hard     1 to 1000")?;
+    writeln!(out, "  4) custom   pick your own range")?;
+
+    let choice = ask(
+        "Enter 1, 2, 3 or 4",
+        |answer| match answer.trim() {
+            "1" | "easy" => Some(Some(Difficulty::Easy)),
+            "2" | "normal" => Some(Some(Difficulty::Normal)),
+            "3" | "hard" => Some(Some(Difficulty::Hard)),
+            "4" | "custom" => Some(None),
+            _ => None,
+        },
+        input,
+        out,
+    )?;
+
+    match choice {
+        Some(Some(difficulty)) => Ok(Some(difficulty)),
+        Some(None) => ask("Enter the range, such as 1-50", parse_range, input, out),
+        None => Ok(None),
+    }
+}
+
+// Blank for no limit
+fn choose_attempts<B: BufRead>(
+    input: &mut io::Lines<B>,
+    out: &mut impl Write,
+) -> io::Result<Option<Option<u32>>> {
+    ask(
+        "How many guesses do you get? Leave blank for as many as you like",
+        |answer| {
+            if answer.trim().is_empty() {
+                Some(None)
+            } else {
+                parse_attempts(answer).map(Some)
+            }
+        },
+        input,
+        out,
+    )
+}
+
+// Plays one game, giving it back once it is over or the input runs out. None
+// if the input ran out before the game could start.
+pub fn run(
+    settings: Settings,
+    input: impl BufRead,
+    mut out: impl Write,
+) -> io::Result<Option<Game>> {
+    let mut input = input.lines();
+
+    writeln!(out, "Guess the number!")?;
+
+    // The menu only asks about what the settings left open
+    let (difficulty, attempts) = match settings.difficulty {
+        Some(difficulty) => (difficulty, settings.attempts),
+        None => {
+            let Some(difficulty) = choose_difficulty(&mut input, &mut out)? else {
+                return Ok(None);
+            };
+            let attempts = match settings.attempts {
+                Some(attempts) => Some(attempts),
+                None => match choose_attempts(&mut input, &mut out)? {
+                    Some(attempts) => attempts,
+                    None => return Ok(None),
+                },
+            };
+            (difficulty, attempts)
+        }
+    };
+
+    let mut game = Game::new(difficulty, attempts, settings.seed);
+    let (low, high) = game.range();
+
+    writeln!(out, "I'm thinking of a number from {low} to {high}.")?;
+    if let Some(attempts) = attempts {
+        writeln!(out, "You have {}.", guesses(attempts))?;
+    }
+
+    if settings.debug {
+        writeln!(out, "[debug] Seed: {}", settings.seed)?;
+        writeln!(out, "[debug] The secret number is: {}", game.secret())?;
+    }
+
+    while !game.is_over() {
+        writeln!(out, "Enter your guess")?;
+
+        let Some(guess) = input.next() else {
+            writeln!(out, "No more guesses. The number was {}.", game.secret())?;
+            break;
+        };
+        let guess = guess?;
+
+        let outcome = game.guess(&guess);
+        if outcome != Outcome::Invalid {
+            writeln!(out, "You guessed: {}", guess.trim())?;
+        }
+
+        match outcome {
+            Outcome::Invalid => writeln!(out, "Please enter a valid number!")?,
+            Outcome::TooSmall => writeln!(out, "Too small!")?,
+            Outcome::TooBig => writeln!(out, "Too big!")?,
+            Outcome::Win => writeln!(out, "You win!")?,
+        }
+
+        if game.is_lost() {
+            writeln!(out, "You lose! The number was {}.", game.secret())?;
+        } else if let (Some(remaining), Outcome::TooSmall | Outcome::TooBig) =
+            (game.remaining(), outcome)
+        {
+            writeln!(out, "{} left.", guesses(remaining))?;
+        }
+    }
+
+    Ok(Some(game))
+}
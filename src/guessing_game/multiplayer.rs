@@ -0,0 +1,258 @@
+// The guessing game for several players at once over TCP, in the manner of the
+// book's web server (https://doc.rust-lang.org/book/ch21-00-final-project-a-web-server.html):
+// each connection is played on its own thread, and all of them guess the same
+// secret. What a player is told goes through a channel to a writer thread of
+// their own, so a slow player never holds up the table. It speaks plain
+// lines, so netcat will do as a client:
+//
+//     nc 127.0.0.1 7878
+//
+// A player gives their name, then sends one guess per line. Every guess and
+// how it compared is told to all the players, apart from guesses that aren't
+// numbers, which only the guesser hears about. The first to guess the secret
+// wins the round, and a new round starts straight away.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::{Difficulty, Game, Outcome};
+
+// A player whose writer has been stuck this long on one line has stopped
+// reading, and is dropped from the table
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// In one write, so a line never goes in pieces
+fn send(mut stream: &TcpStream, message: &str) -> io::Result<()> {
+    stream.write_all(format!("{message}\n").as_bytes())
+}
+
+struct Player {
+    name: String,
+    // To the player's writer thread, which stops once this is dropped
+    outbox: Sender<String>,
+}
+
+struct Table {
+    difficulty: Difficulty,
+    // Picks each round's secret
+    rng: ChaCha8Rng,
+    game: Game,
+    round: u32,
+    players: BTreeMap<u64, Player>,
+    next_id: u64,
+}
+
+impl Table {
+    // Only queues the message, so nothing waits on the network under the lock.
+    // A player whose writer has stopped is gone, and is removed.
+    fn post(&mut self, message: &str, to: impl Fn(u64) -> bool) {
+        self.players
+            .retain(|&id, player| !to(id) || player.outbox.send(message.to_string()).is_ok());
+    }
+
+    fn tell(&mut self, id: u64, message: &str) {
+        self.post(message, |player| player == id);
+    }
+
+    fn tell_all(&mut self, message: &str) {
+        self.post(message, |_| true);
+    }
+
+    fn tell_others(&mut self, id: u64, message: &str) {
+        self.post(message, |player| player != id);
+    }
+
+    fn round_message(&self) -> String {
+        let (low, high) = self.game.range();
+        format!(
+            "Round {}: guess the number from {low} to {high}.",
+            self.round
+        )
+    }
+
+    fn new_round(&mut self) {
+        self.round += 1;
+        self.game = Game::new(self.difficulty, None, self.rng.random());
+        let message = self.round_message();
+        self.tell_all(&message);
+    }
+
+    // The name, or the name with a number after it if someone already has it
+    fn unique_name(&self, name: &str) -> String {
+        let taken = |candidate: &str| self.players.values().any(|p| p.name == candidate);
+        if !taken(name) {
+            return name.to_string();
+        }
+        (2..)
+            .map(|n| format!("{name} ({n})"))
+            .find(|candidate| !taken(candidate))
+            .expect("there is always another number")
+    }
+}
+
+#[derive(Clone)]
+pub struct Server {
+    table: Arc<Mutex<Table>>,
+}
+
+impl Server {
+    // The same seed always gives the same secrets, round after round
+    pub fn new(difficulty: Difficulty, seed: u64) -> Server {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let game = Game::new(difficulty, None, rng.random());
+
+        Server {
+            table: Arc::new(Mutex::new(Table {
+                difficulty,
+                rng,
+                game,
+                round: 1,
+                players: BTreeMap::new(),
+                next_id: 0,
+            })),
+        }
+    }
+
+    // A player's thread panicking mid message leaves nothing half done
+    fn table(&self) -> MutexGuard<'_, Table> {
+        self.table.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn round(&self) -> u32 {
+        self.table().round
+    }
+
+    // The names of everyone playing, in the order they joined
+    pub fn players(&self) -> Vec<String> {
+        self.table()
+            .players
+            .values()
+            .map(|player| player.name.clone())
+            .collect()
+    }
+
+    // Plays each connection on a thread of its own, for as long as the
+    // listener accepts them. A connection that fails before it is accepted is
+    // only reported, so the players already at the table play on.
+    pub fn serve(&self, listener: TcpListener) {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let server = self.clone();
+                    // A player who goes away is only their own problem
+                    thread::spawn(move || server.play(stream));
+                }
+                Err(e) => eprintln!("Can't accept a player: {e}"),
+            }
+        }
+    }
+
+    fn play(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        // Lines are small and should go straight away
+        stream.set_nodelay(true)?;
+        let mut lines = BufReader::new(stream.try_clone()?).lines();
+
+        send(&stream, "Welcome to the guessing game! What's your name?")?;
+        let name = loop {
+            let Some(line) = lines.next() else {
+                return Ok(());
+            };
+            let name = line?.trim().to_string();
+            if !name.is_empty() {
+                break name;
+            }
+            send(&stream, "What's your name?")?;
+        };
+
+        let writer = stream.try_clone()?;
+        let (outbox, messages) = mpsc::channel();
+        let id = self.join(&name, outbox);
+        let server = self.clone();
+        thread::spawn(move || server.write(id, writer, messages));
+
+        let played = lines.try_for_each(|line| line.map(|guess| self.guess(id, &guess)));
+        self.leave(id);
+        played
+    }
+
+    // Sends the player what they are told until they leave. If a write fails
+    // they can't be playing any more, so they are dropped, and their
+    // connection is shut down to stop their reading thread too.
+    fn write(&self, id: u64, stream: TcpStream, messages: Receiver<String>) {
+        for message in messages {
+            if send(&stream, &message).is_err() {
+                let _ = stream.shutdown(Shutdown::Both);
+                self.leave(id);
+                return;
+            }
+        }
+    }
+
+    fn join(&self, name: &str, outbox: Sender<String>) -> u64 {
+        let mut table = self.table();
+        let id = table.next_id;
+        table.next_id += 1;
+
+        let name = table.unique_name(name);
+        table.players.insert(
+            id,
+            Player {
+                name: name.clone(),
+                outbox,
+            },
+        );
+
+        let welcome = format!("Hi {name}. {}", table.round_message());
+        table.tell(id, &welcome);
+        table.tell_others(id, &format!("{name} joined."));
+        id
+    }
+
+    fn guess(&self, id: u64, guess: &str) {
+        let mut table = self.table();
+        let Some(name) = table.players.get(&id).map(|player| player.name.clone()) else {
+            return;
+        };
+
+        let guess = guess.trim();
+        match table.game.guess(guess) {
+            Outcome::Invalid => table.tell(id, "Please enter a valid number!"),
+            Outcome::TooSmall => table.tell_all(&format!("{name} guessed {guess}: Too small!")),
+            Outcome::TooBig => table.tell_all(&format!("{name} guessed {guess}: Too big!")),
+            Outcome::Win => {
+                let round = table.round;
+                table.tell_all(&format!(
+                    "{name} guessed {guess}: Correct! {name} wins round {round}."
+                ));
+                table.new_round();
+            }
+        }
+    }
+
+    fn leave(&self, id: u64) {
+        let mut table = self.table();
+        if let Some(player) = table.players.remove(&id) {
+            table.tell_all(&format!("{} left.", player.name));
+        }
+    }
+}
+
+impl fmt::Debug for Server {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let table = self.table();
+        f.debug_struct("Server")
+            .field("difficulty", &table.difficulty)
+            .field("round", &table.round)
+            .field("players", &table.players.len())
+            .finish_non_exhaustive()
+    }
+}
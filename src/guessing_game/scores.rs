@@ -0,0 +1,207 @@
+// A high-score table per difficulty, kept in a file one won game per line:
+// seconds,difficulty,guesses,milliseconds. Fewer guesses rank higher, then a
+// quicker game, then an earlier one. The file is only ever appended to, and a
+// line that can't be read, say from a write cut short, is skipped rather than
+// spoiling the rest.
+use std::cmp::Ordering;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::Game;
+
+// How many places the table shows
+pub const PLACES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Score {
+    // Seconds since the Unix epoch
+    pub when: u64,
+    // As `Difficulty::name` gives it
+    pub difficulty: String,
+    pub guesses: u32,
+    pub elapsed: Duration,
+}
+
+impl Score {
+    // The score for a game that has been won, made now
+    pub fn of(game: &Game) -> Score {
+        let when = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+
+        Score {
+            when,
+            difficulty: game.difficulty().name(),
+            guesses: game.guessed(),
+            elapsed: game.elapsed(),
+        }
+    }
+
+    fn line(&self) -> String {
+        format!(
+            "{},{},{},{}",
+            self.when,
+            self.difficulty,
+            self.guesses,
+            self.elapsed.as_millis()
+        )
+    }
+
+    fn parse(line: &str) -> Option<Score> {
+        let fields: Vec<&str> = line.split(',').collect();
+        let [when, difficulty, guesses, millis] = fields.as_slice() else {
+            return None;
+        };
+        if difficulty.is_empty() {
+            return None;
+        }
+
+        Some(Score {
+            when: when.parse().ok()?,
+            difficulty: difficulty.to_string(),
+            guesses: guesses.parse().ok()?,
+            elapsed: Duration::from_millis(millis.parse().ok()?),
+        })
+    }
+
+    // Less for the better score
+    fn compare(&self, other: &Score) -> Ordering {
+        (self.guesses, self.elapsed, self.when).cmp(&(other.guesses, other.elapsed, other.when))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HighScores {
+    scores: Vec<Score>,
+    // Lines in the file that couldn't be read
+    skipped: usize,
+}
+
+impl HighScores {
+    // No file yet is the same as an empty one
+    pub fn load(path: &Path) -> io::Result<HighScores> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HighScores::default()),
+            Err(e) => return Err(e),
+        };
+
+        let mut high_scores = HighScores::default();
+        for line in String::from_utf8_lossy(&bytes).lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match Score::parse(line.trim()) {
+                Some(score) => high_scores.scores.push(score),
+                None => high_scores.skipped += 1,
+            }
+        }
+
+        Ok(high_scores)
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    pub fn add(&mut self, score: Score) {
+        self.scores.push(score);
+    }
+
+    // Every score for the difficulty, best first
+    pub fn table(&self, difficulty: &str) -> Vec<&Score> {
+        let mut table: Vec<&Score> = self
+            .scores
+            .iter()
+            .filter(|score| score.difficulty == difficulty)
+            .collect();
+        table.sort_by(|a, b| a.compare(b));
+        table
+    }
+
+    // Where the score places in its difficulty's table, counting from 1, or
+    // None if it isn't in the table
+    pub fn rank(&self, score: &Score) -> Option<usize> {
+        self.table(&score.difficulty)
+            .iter()
+            .position(|entry| *entry == score)
+            .map(|place| place + 1)
+    }
+
+    // The top places for the score's difficulty, marking the score, and where
+    // it placed. A score outside the top places is shown after them.
+    pub fn write_table(&self, score: &Score, out: &mut impl Write) -> io::Result<()> {
+        let table = self.table(&score.difficulty);
+        let rank = self.rank(score);
+
+        writeln!(out, "\nHigh scores for {}:", score.difficulty)?;
+        writeln!(out, "{:>6}  {:>7}  {:>7}", "Place", "Guesses", "Time")?;
+
+        let row = |out: &mut dyn Write, place: usize, entry: &Score| {
+            let marker = if rank == Some(place) {
+                "  <- this game"
+            } else {
+                ""
+            };
+            writeln!(
+                out,
+                "{place:>6}  {:>7}  {:>7}{marker}",
+                entry.guesses,
+                duration(entry.elapsed)
+            )
+        };
+
+        for (i, entry) in table.iter().take(PLACES).enumerate() {
+            row(out, i + 1, entry)?;
+        }
+        if let Some(rank) = rank.filter(|&rank| rank > PLACES) {
+            writeln!(out, "{:>6}", "...")?;
+            row(out, rank, score)?;
+        }
+
+        if let Some(rank) = rank {
+            writeln!(out, "You placed {} of {}.", ordinal(rank), table.len())?;
+        }
+        Ok(())
+    }
+}
+
+// Appends the score to the file, first finishing off any line a cut short
+// write left behind, so as not to join the score onto it
+pub fn save(path: &Path, score: &Score) -> io::Result<()> {
+    let ends_cleanly = match fs::read(path) {
+        Ok(bytes) => bytes.last().is_none_or(|&last| last == b'\n'),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => true,
+        Err(e) => return Err(e),
+    };
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if !ends_cleanly {
+        writeln!(file)?;
+    }
+    writeln!(file, "{}", score.line())
+}
+
+// Whole seconds, as a game's length in tenths of a second is noise
+fn duration(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    if seconds < 60 {
+        format!("{seconds}s")
+    } else {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
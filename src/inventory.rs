@@ -0,0 +1,330 @@
+// The shirt inventory from the closures chapter, with real stock counts:
+// https://doc.rust-lang.org/book/ch13-01-closures.html
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+pub mod clock;
+pub mod ledger;
+pub mod policy;
+pub mod report;
+mod reservation;
+pub mod server;
+pub mod shared;
+mod shirt;
+pub mod shop;
+pub mod store;
+
+pub use reservation::{Reservation, ReservationError};
+pub use shirt::{ParseShirtError, Preference, Shirt, ShirtColour, ShirtStyle, Size};
+
+use clock::{Clock, SystemClock};
+use policy::{AllocationPolicy, MostStocked};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GiveawayError {
+    // Nothing the user would accept is left
+    OutOfStock(Preference),
+    // Nothing left at all
+    SoldOut,
+}
+
+impl fmt::Display for GiveawayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GiveawayError::OutOfStock(preference) => {
+                write!(f, "there are no {preference} shirts left")
+            }
+            GiveawayError::SoldOut => write!(f, "there are no shirts left"),
+        }
+    }
+}
+
+impl Error for GiveawayError {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RestockError {
+    // There would be more of the shirt than a count can hold
+    TooMany {
+        shirt: Shirt,
+        stock: u32,
+        count: u32,
+    },
+}
+
+impl fmt::Display for RestockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestockError::TooMany {
+                shirt,
+                stock,
+                count,
+            } => write!(
+                f,
+                "can't add {count} {shirt} shirts to the {stock} in stock, as that is more than {} in all",
+                u32::MAX
+            ),
+        }
+    }
+}
+
+impl Error for RestockError {}
+
+#[derive(Debug)]
+pub struct Inventory {
+    // Every shirt on the shelves, held or not
+    stock: BTreeMap<Shirt, u32>,
+    // Picks the shirt when more than one would suit the user
+    policy: Box<dyn AllocationPolicy>,
+    // By id. Expired holds linger here until the next change, but are never
+    // counted as held.
+    reservations: BTreeMap<u64, Reservation>,
+    next_reservation: u64,
+    clock: Box<dyn Clock>,
+}
+
+// What an inventory was like before a change, so the change can be undone
+pub(crate) struct Checkpoint {
+    stock: BTreeMap<Shirt, u32>,
+    policy: Box<dyn AllocationPolicy>,
+    reservations: BTreeMap<u64, Reservation>,
+    next_reservation: u64,
+}
+
+impl Default for Inventory {
+    fn default() -> Inventory {
+        Inventory::with_policy(MostStocked::default())
+    }
+}
+
+impl Inventory {
+    pub fn new() -> Inventory {
+        Inventory::default()
+    }
+
+    pub fn with_policy(policy: impl AllocationPolicy + 'static) -> Inventory {
+        Inventory {
+            stock: BTreeMap::new(),
+            policy: Box::new(policy),
+            reservations: BTreeMap::new(),
+            next_reservation: 1,
+            clock: Box::new(SystemClock),
+        }
+    }
+
+    pub fn set_policy(&mut self, policy: impl AllocationPolicy + 'static) {
+        self.policy = Box::new(policy);
+    }
+
+    pub fn policy_name(&self) -> &'static str {
+        self.policy.name()
+    }
+
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+    }
+
+    // On the shelves, including any that are held
+    pub fn stock(&self, shirt: &Shirt) -> u32 {
+        self.stock.get(shirt).copied().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u32 {
+        self.stock.values().sum()
+    }
+
+    // Every shirt the inventory has ever stocked, including those that have run out
+    pub fn stock_levels(&self) -> impl Iterator<Item = (&Shirt, u32)> {
+        self.stock.iter().map(|(shirt, &count)| (shirt, count))
+    }
+
+    // How many of this shirt are being held for someone
+    pub fn held(&self, shirt: &Shirt) -> u32 {
+        let now = self.clock.now();
+        self.reservations
+            .values()
+            .filter(|reservation| reservation.shirt == *shirt && !reservation.is_expired(now))
+            .count() as u32
+    }
+
+    // The holds that haven't run out, oldest first
+    pub fn reservations(&self) -> Vec<&Reservation> {
+        let now = self.clock.now();
+        self.reservations
+            .values()
+            .filter(|reservation| !reservation.is_expired(now))
+            .collect()
+    }
+
+    // The shirts that would suit the preference and aren't held for anyone,
+    // with how many of each are free
+    pub fn available(&self, preference: &Preference) -> Vec<(Shirt, u32)> {
+        self.stock
+            .iter()
+            .filter(|&(shirt, _)| preference.matches(shirt))
+            .map(|(shirt, &count)| (shirt.clone(), count.saturating_sub(self.held(shirt))))
+            .filter(|&(_, free)| free > 0)
+            .collect()
+    }
+
+    // Leaves the stock as it was if the count would overflow
+    pub fn restock(&mut self, shirt: Shirt, count: u32) -> Result<(), RestockError> {
+        let stock = self.stock(&shirt);
+        let Some(total) = stock.checked_add(count) else {
+            return Err(RestockError::TooMany {
+                shirt,
+                stock,
+                count,
+            });
+        };
+
+        self.stock.insert(shirt, total);
+        Ok(())
+    }
+
+    // Picks a free shirt that suits the preference, without taking it
+    fn choose(&mut self, preference: &Preference) -> Result<Shirt, GiveawayError> {
+        let candidates = self.available(preference);
+
+        self.policy.choose(&candidates).ok_or_else(|| {
+            if self.available(&Preference::any()).is_empty() {
+                GiveawayError::SoldOut
+            } else {
+                GiveawayError::OutOfStock(preference.clone())
+            }
+        })
+    }
+
+    // Hands out a shirt that suits the user's preference, letting the allocation
+    // policy pick when several would, and takes it out of stock. Shirts held
+    // for other users are left alone.
+    pub fn giveaway(&mut self, preference: &Preference) -> Result<Shirt, GiveawayError> {
+        self.expire();
+        let shirt = self.choose(preference)?;
+
+        if !self.take(&shirt) {
+            return Err(GiveawayError::OutOfStock(preference.clone()));
+        }
+        self.policy.given(&shirt);
+        Ok(shirt)
+    }
+
+    // Holds a shirt that suits the preference for the user, for as long as
+    // `hold`, to the nearest second below
+    pub fn reserve(
+        &mut self,
+        user: &str,
+        preference: &Preference,
+        hold: Duration,
+    ) -> Result<Reservation, ReservationError> {
+        if hold.as_secs() == 0 {
+            return Err(ReservationError::TooShort(hold));
+        }
+        self.expire();
+        let shirt = self.choose(preference)?;
+
+        let reservation = Reservation {
+            id: self.next_reservation,
+            user: user.to_string(),
+            preference: preference.clone(),
+            shirt,
+            expires: self.clock.now().saturating_add(hold.as_secs()),
+        };
+        self.next_reservation += 1;
+        self.reservations
+            .insert(reservation.id, reservation.clone());
+        Ok(reservation)
+    }
+
+    // The user is back: hands over the shirt held for them
+    pub fn confirm(&mut self, id: u64) -> Result<Reservation, ReservationError> {
+        self.expire();
+        let reservation = self
+            .reservations
+            .remove(&id)
+            .ok_or(ReservationError::NotHeld(id))?;
+
+        if !self.take(&reservation.shirt) {
+            return Err(ReservationError::Gone {
+                id,
+                shirt: reservation.shirt,
+            });
+        }
+        self.policy.given(&reservation.shirt);
+        Ok(reservation)
+    }
+
+    // Puts the held shirt back for anyone to have
+    pub fn cancel(&mut self, id: u64) -> Result<Reservation, ReservationError> {
+        self.expire();
+        self.reservations
+            .remove(&id)
+            .ok_or(ReservationError::NotHeld(id))
+    }
+
+    // Drops the holds that have run out, returning their shirts to stock
+    pub fn expire(&mut self) -> Vec<Reservation> {
+        let now = self.clock.now();
+        let expired: Vec<u64> = self
+            .reservations
+            .values()
+            .filter(|reservation| reservation.is_expired(now))
+            .map(|reservation| reservation.id)
+            .collect();
+
+        expired
+            .iter()
+            .filter_map(|id| self.reservations.remove(id))
+            .collect()
+    }
+
+    pub(crate) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            stock: self.stock.clone(),
+            policy: self.policy.boxed_clone(),
+            reservations: self.reservations.clone(),
+            next_reservation: self.next_reservation,
+        }
+    }
+
+    // Puts everything but the clock back as it was at the checkpoint
+    pub(crate) fn restore(&mut self, checkpoint: Checkpoint) {
+        self.stock = checkpoint.stock;
+        self.policy = checkpoint.policy;
+        self.reservations = checkpoint.reservations;
+        self.next_reservation = checkpoint.next_reservation;
+    }
+
+    // Takes one of this shirt out of stock without asking the policy, as when
+    // replaying a record of giveaways. False if there were none to take.
+    pub fn take(&mut self, shirt: &Shirt) -> bool {
+        match self.stock.get_mut(shirt) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // The free shirt there is most of, with ties going to blue as in the book.
+    // None once everything has gone or is held.
+    pub fn most_stocked(&self) -> Option<Shirt> {
+        MostStocked::default().choose(&self.available(&Preference::any()))
+    }
+}
+
+// One shirt in stock for each one in the iterator, in the spirit of the book's
+// `vec![ShirtColour::Blue, ShirtColour::Red, ShirtColour::Blue]`
+impl FromIterator<Shirt> for Inventory {
+    fn from_iter<I: IntoIterator<Item = Shirt>>(shirts: I) -> Inventory {
+        let mut inventory = Inventory::new();
+        for shirt in shirts {
+            inventory
+                .restock(shirt, 1)
+                .expect("fewer than u32::MAX of each shirt");
+        }
+        inventory
+    }
+}
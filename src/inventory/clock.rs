@@ -0,0 +1,47 @@
+// Where an inventory gets the time from, so that holds can be tested without
+// waiting for them to run out
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use super::ledger;
+
+pub trait Clock: fmt::Debug + Send {
+    // Seconds since the Unix epoch
+    fn now(&self) -> u64;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        ledger::now()
+    }
+}
+
+// A clock that only moves when told to. Clones share the same time, so a test
+// can keep one and hand the other to the inventory.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    pub fn starting_at(now: u64) -> ManualClock {
+        ManualClock {
+            now: Arc::new(AtomicU64::new(now)),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now.fetch_add(by.as_secs(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,262 @@
+// An append-only record of everything that has happened to the stock, one JSON
+// object per line:
+//
+//     {"time":1717200000,"event":"stocktake","stock":{"red/M/crew":10}}
+//     {"time":1717200060,"event":"giveaway","user":"ferris","preference":"*/M/*","shirt":"red/M/crew","policy":"most-stocked"}
+//     {"time":1717200120,"event":"refused","user":"corro","preference":"blue/*/*"}
+//
+// Replaying it rebuilds the stock as it was after any entry, and auditing it
+// finds entries that don't add up.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::store::{StoreError, json_error};
+use super::{Inventory, Preference, Shirt};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event {
+    // The stock was counted, or imported from a sheet, and is now exactly this
+    Stocktake {
+        stock: BTreeMap<Shirt, u32>,
+    },
+    Restock {
+        shirt: Shirt,
+        count: u32,
+    },
+    Giveaway {
+        user: String,
+        preference: Preference,
+        shirt: Shirt,
+        policy: String,
+    },
+    // Nothing the user would accept was free, so they went without
+    Refused {
+        user: String,
+        preference: Preference,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    // Seconds since the Unix epoch
+    pub time: u64,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+impl Event {
+    // Everything in the inventory as it stands, including shirts that have run out
+    pub fn stocktake(inventory: &Inventory) -> Event {
+        let stock = inventory
+            .stock_levels()
+            .map(|(shirt, count)| (shirt.clone(), count))
+            .collect();
+        Event::Stocktake { stock }
+    }
+}
+
+impl Entry {
+    pub fn now(event: Event) -> Entry {
+        Entry { time: now(), event }
+    }
+}
+
+pub struct Ledger {
+    path: PathBuf,
+}
+
+impl Ledger {
+    pub fn new(path: impl Into<PathBuf>) -> Ledger {
+        Ledger { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    // Entries are only ever added to the end, each with a single write, so an
+    // entry is either there in full or not at all
+    pub fn append(&self, entry: &Entry) -> Result<(), StoreError> {
+        let mut line = serde_json::to_string(entry).map_err(json_error)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    // Every entry in order, or the first line that can't be read. A ledger
+    // that doesn't exist yet has no entries.
+    pub fn entries(&self) -> Result<Vec<Entry>, StoreError> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(StoreError::Io(e)),
+        };
+
+        read(&text)
+    }
+}
+
+pub fn read(text: &str) -> Result<Vec<Entry>, StoreError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| match json_error(e) {
+                StoreError::Invalid { message, .. } => StoreError::Invalid {
+                    line: i + 1,
+                    message,
+                },
+                e => e,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Until {
+    // After this many entries
+    Entry(usize),
+    // After every entry recorded at or before this time. The ledger is in time
+    // order, so replay stops at the first entry after it.
+    Time(u64),
+    End,
+}
+
+// Applies the event as it was recorded, rather than asking the allocation
+// policy again. Returns false for a giveaway of a shirt there was none of, or
+// a restock of more than could be counted.
+pub(crate) fn apply(inventory: &mut Inventory, event: &Event) -> bool {
+    match event {
+        Event::Stocktake { stock } => {
+            *inventory = Inventory::new();
+            stock
+                .iter()
+                .all(|(shirt, &count)| inventory.restock(shirt.clone(), count).is_ok())
+        }
+        Event::Restock { shirt, count } => inventory.restock(shirt.clone(), *count).is_ok(),
+        Event::Giveaway { shirt, .. } => inventory.take(shirt),
+        Event::Refused { .. } => true,
+    }
+}
+
+// The stock as it stood at `until`. Giveaways of stock that wasn't there are
+// skipped; `audit` reports them.
+pub fn replay(entries: &[Entry], until: Until) -> Inventory {
+    let count = match until {
+        Until::Entry(count) => count,
+        Until::Time(time) => entries
+            .iter()
+            .position(|entry| entry.time > time)
+            .unwrap_or(entries.len()),
+        Until::End => entries.len(),
+    };
+
+    let mut inventory = Inventory::new();
+    for entry in entries.iter().take(count) {
+        apply(&mut inventory, &entry.event);
+    }
+    inventory
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    // One based, counting entries rather than lines
+    pub entry: usize,
+    pub problem: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "entry {}: {}", self.entry, self.problem)
+    }
+}
+
+// Who a giveaway went to, for messages about it
+fn who(user: &str) -> &str {
+    if user.trim().is_empty() {
+        "someone"
+    } else {
+        user
+    }
+}
+
+// Everything in the ledger that doesn't add up
+pub fn audit(entries: &[Entry]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut inventory = Inventory::new();
+    let mut previous_time = 0;
+
+    for (i, entry) in entries.iter().enumerate() {
+        let mut problem = |problem: String| {
+            issues.push(Issue {
+                entry: i + 1,
+                problem,
+            })
+        };
+
+        if entry.time < previous_time {
+            problem(format!(
+                "recorded at {}, before the entry above it at {previous_time}",
+                entry.time
+            ));
+        }
+        previous_time = previous_time.max(entry.time);
+
+        let applied = apply(&mut inventory, &entry.event);
+
+        match &entry.event {
+            Event::Giveaway {
+                user,
+                preference,
+                shirt,
+                ..
+            } => {
+                if user.trim().is_empty() {
+                    problem(format!("{shirt} was given to nobody in particular"));
+                }
+                if !preference.matches(shirt) {
+                    problem(format!(
+                        "{} asked for {preference} but was given {shirt}",
+                        who(user)
+                    ));
+                }
+                if !applied {
+                    problem(format!(
+                        "{shirt} was given to {} when there were none in stock",
+                        who(user)
+                    ));
+                }
+            }
+            Event::Restock { shirt, count } if !applied => problem(format!(
+                "restocking {count} {shirt} would make more than {} of them",
+                u32::MAX
+            )),
+            _ => {}
+        }
+    }
+
+    issues
+}
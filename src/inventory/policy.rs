@@ -0,0 +1,225 @@
+// How `Inventory::giveaway` picks a shirt when more than one would suit the
+// user. Every policy only ever sees shirts that are in stock and match the
+// user's preference.
+use std::collections::BTreeMap;
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::{Shirt, ShirtColour};
+
+pub trait AllocationPolicy: fmt::Debug + Send {
+    // Picks one of the shirts in `available`, each listed once with a stock
+    // count above zero, in Shirt order. None only if `available` is empty.
+    fn choose(&mut self, available: &[(Shirt, u32)]) -> Option<Shirt>;
+
+    // Called after every shirt handed out
+    fn given(&mut self, _shirt: &Shirt) {}
+
+    // A short name for reports, e.g. "most-stocked"
+    fn name(&self) -> &'static str;
+
+    // A copy of the policy as it stands, so that a giveaway that couldn't be
+    // recorded can be undone along with whatever the policy made of it
+    fn boxed_clone(&self) -> Box<dyn AllocationPolicy>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TieBreak {
+    // Give a shirt of this colour when one is among the most stocked,
+    // otherwise the first of them
+    Prefer(ShirtColour),
+    // Take turns between the shirts that are tied
+    Alternate,
+}
+
+// The book's policy: whichever shirt there is most of
+#[derive(Debug, Clone)]
+pub struct MostStocked {
+    tie_break: TieBreak,
+    last_tie: Option<Shirt>,
+}
+
+impl MostStocked {
+    pub fn new(tie_break: TieBreak) -> MostStocked {
+        MostStocked {
+            tie_break,
+            last_tie: None,
+        }
+    }
+}
+
+// Ties go to Blue, as they always have in the book's version
+impl Default for MostStocked {
+    fn default() -> MostStocked {
+        MostStocked::new(TieBreak::Prefer(ShirtColour::new("blue")))
+    }
+}
+
+impl AllocationPolicy for MostStocked {
+    fn choose(&mut self, available: &[(Shirt, u32)]) -> Option<Shirt> {
+        let most = available.iter().map(|(_, count)| *count).max()?;
+        let tied: Vec<&Shirt> = available
+            .iter()
+            .filter(|(_, count)| *count == most)
+            .map(|(shirt, _)| shirt)
+            .collect();
+
+        let shirt = match &self.tie_break {
+            TieBreak::Prefer(colour) => tied
+                .iter()
+                .find(|shirt| shirt.colour == *colour)
+                .unwrap_or(&tied[0]),
+            TieBreak::Alternate => {
+                // The first tied shirt after the one picked at the last tie
+                let shirt = self
+                    .last_tie
+                    .as_ref()
+                    .and_then(|last| tied.iter().find(|shirt| **shirt > last))
+                    .unwrap_or(&tied[0]);
+                if tied.len() > 1 {
+                    self.last_tie = Some((*shirt).clone());
+                }
+                shirt
+            }
+        };
+
+        Some((*shirt).clone())
+    }
+
+    fn name(&self) -> &'static str {
+        "most-stocked"
+    }
+
+    fn boxed_clone(&self) -> Box<dyn AllocationPolicy> {
+        Box::new(self.clone())
+    }
+}
+
+// Each shirt in turn, skipping any that have run out
+#[derive(Debug, Clone, Default)]
+pub struct RoundRobin {
+    last: Option<Shirt>,
+}
+
+impl RoundRobin {
+    pub fn new() -> RoundRobin {
+        RoundRobin::default()
+    }
+}
+
+impl AllocationPolicy for RoundRobin {
+    fn choose(&mut self, available: &[(Shirt, u32)]) -> Option<Shirt> {
+        let (first, _) = available.first()?;
+        let shirt = self
+            .last
+            .as_ref()
+            .and_then(|last| available.iter().find(|(shirt, _)| shirt > last))
+            .map_or(first, |(shirt, _)| shirt)
+            .clone();
+
+        self.last = Some(shirt.clone());
+        Some(shirt)
+    }
+
+    fn name(&self) -> &'static str {
+        "round-robin"
+    }
+
+    fn boxed_clone(&self) -> Box<dyn AllocationPolicy> {
+        Box::new(self.clone())
+    }
+}
+
+// A random shirt, with the chance of each in proportion to how many are left
+#[derive(Debug, Clone)]
+pub struct WeightedRandom {
+    rng: StdRng,
+}
+
+impl WeightedRandom {
+    pub fn new() -> WeightedRandom {
+        WeightedRandom {
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    // The same seed always gives the same sequence of shirts
+    pub fn seeded(seed: u64) -> WeightedRandom {
+        WeightedRandom {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for WeightedRandom {
+    fn default() -> WeightedRandom {
+        WeightedRandom::new()
+    }
+}
+
+impl AllocationPolicy for WeightedRandom {
+    fn choose(&mut self, available: &[(Shirt, u32)]) -> Option<Shirt> {
+        let total: u32 = available.iter().map(|(_, count)| count).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut pick = self.rng.random_range(0..total);
+        for (shirt, count) in available {
+            if pick < *count {
+                return Some(shirt.clone());
+            }
+            pick -= count;
+        }
+
+        None
+    }
+
+    fn name(&self) -> &'static str {
+        "weighted-random"
+    }
+
+    fn boxed_clone(&self) -> Box<dyn AllocationPolicy> {
+        Box::new(self.clone())
+    }
+}
+
+// The shirt that has gone the longest without being given away
+#[derive(Debug, Clone, Default)]
+pub struct LeastRecentlyGiven {
+    given: u64,
+    // The value of `given` when each shirt was last handed out
+    last_given: BTreeMap<Shirt, u64>,
+}
+
+impl LeastRecentlyGiven {
+    pub fn new() -> LeastRecentlyGiven {
+        LeastRecentlyGiven::default()
+    }
+}
+
+impl AllocationPolicy for LeastRecentlyGiven {
+    fn choose(&mut self, available: &[(Shirt, u32)]) -> Option<Shirt> {
+        // Never given counts as longest ago; min_by_key keeps the first of any ties
+        available
+            .iter()
+            .map(|(shirt, _)| shirt)
+            .min_by_key(|shirt| self.last_given.get(*shirt).copied().unwrap_or(0))
+            .cloned()
+    }
+
+    fn given(&mut self, shirt: &Shirt) {
+        self.given += 1;
+        self.last_given.insert(shirt.clone(), self.given);
+    }
+
+    fn name(&self) -> &'static str {
+        "least-recently-given"
+    }
+
+    fn boxed_clone(&self) -> Box<dyn AllocationPolicy> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,447 @@
+// How fair a giveaway was, worked out from its ledger: how many users stated
+// a preference and how many of those got it, how the stock ran down over the
+// event, and how much of each colour went.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use serde_json::{Value, json};
+
+use super::ledger::{self, Entry, Event};
+use super::{Inventory, ShirtColour};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    // Everyone who asked for a shirt, whether or not they got one
+    pub requests: u32,
+    // Asked for a particular colour, size or style
+    pub stated: u32,
+    // Stated a preference and were given a shirt that suits it
+    pub honoured: u32,
+    // Didn't mind, so took whatever the allocation policy picked
+    pub fallback: u32,
+    // Went without
+    pub refused: u32,
+}
+
+impl Summary {
+    // The share of stated preferences that were honoured, if any were stated
+    pub fn honoured_rate(&self) -> Option<f64> {
+        (self.stated > 0).then(|| f64::from(self.honoured) / f64::from(self.stated))
+    }
+}
+
+// How far one colour ran down, across all its sizes and styles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Depletion {
+    pub colour: ShirtColour,
+    // Every shirt that came into stock: restocks, and whatever a stocktake
+    // counted on top of what was there before it
+    pub stocked: u32,
+    pub given: u32,
+    pub left: u32,
+    // The entry after which none were left, if there are still none left
+    pub ran_out: Option<usize>,
+}
+
+impl Depletion {
+    // The share of the stock that was given away
+    pub fn depleted(&self) -> f64 {
+        if self.stocked == 0 {
+            return 0.0;
+        }
+        f64::from(self.given) / f64::from(self.stocked)
+    }
+}
+
+// The stock after one entry of the ledger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Level {
+    // One based, as replay counts them
+    pub entry: usize,
+    pub time: u64,
+    pub total: u32,
+    // Every colour in the ledger, including those there are none of yet
+    pub colours: BTreeMap<ShirtColour, u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub summary: Summary,
+    // In colour order
+    pub colours: Vec<Depletion>,
+    pub timeline: Vec<Level>,
+}
+
+fn colour_levels(inventory: &Inventory, colours: &[ShirtColour]) -> BTreeMap<ShirtColour, u32> {
+    let mut levels: BTreeMap<ShirtColour, u32> =
+        colours.iter().map(|colour| (colour.clone(), 0)).collect();
+    for (shirt, count) in inventory.stock_levels() {
+        *levels.entry(shirt.colour.clone()).or_insert(0) += count;
+    }
+    levels
+}
+
+// Every colour the ledger mentions
+fn colours(entries: &[Entry]) -> Vec<ShirtColour> {
+    let mut colours: Vec<ShirtColour> = entries
+        .iter()
+        .flat_map(|entry| match &entry.event {
+            Event::Stocktake { stock } => stock.keys().map(|shirt| &shirt.colour).collect(),
+            Event::Restock { shirt, .. } | Event::Giveaway { shirt, .. } => vec![&shirt.colour],
+            Event::Refused { .. } => vec![],
+        })
+        .cloned()
+        .collect();
+    colours.sort();
+    colours.dedup();
+    colours
+}
+
+pub fn report(entries: &[Entry]) -> Report {
+    let colours = colours(entries);
+    let mut summary = Summary::default();
+    let mut stocked: BTreeMap<ShirtColour, u32> = BTreeMap::new();
+    let mut given: BTreeMap<ShirtColour, u32> = BTreeMap::new();
+    let mut ran_out: BTreeMap<ShirtColour, usize> = BTreeMap::new();
+    let mut timeline = Vec::new();
+
+    let mut inventory = Inventory::new();
+    let mut previous = colour_levels(&inventory, &colours);
+    for (i, entry) in entries.iter().enumerate() {
+        match &entry.event {
+            Event::Giveaway {
+                preference, shirt, ..
+            } => {
+                summary.requests += 1;
+                if preference.is_any() {
+                    summary.fallback += 1;
+                } else {
+                    summary.stated += 1;
+                    if preference.matches(shirt) {
+                        summary.honoured += 1;
+                    }
+                }
+                *given.entry(shirt.colour.clone()).or_insert(0) += 1;
+            }
+            Event::Refused { preference, .. } => {
+                summary.requests += 1;
+                summary.refused += 1;
+                if preference.is_any() {
+                    summary.fallback += 1;
+                } else {
+                    summary.stated += 1;
+                }
+            }
+            Event::Stocktake { stock } => {
+                // Shirts that turned up; any that went missing weren't given
+                for (shirt, &count) in stock {
+                    let found = count.saturating_sub(inventory.stock(shirt));
+                    *stocked.entry(shirt.colour.clone()).or_insert(0) += found;
+                }
+            }
+            Event::Restock { .. } => {}
+        }
+        // A restock that couldn't be counted brought nothing in
+        if ledger::apply(&mut inventory, &entry.event)
+            && let Event::Restock { shirt, count } = &entry.event
+        {
+            *stocked.entry(shirt.colour.clone()).or_insert(0) += count;
+        }
+
+        let levels = colour_levels(&inventory, &colours);
+        for (colour, &count) in &levels {
+            if count > 0 {
+                ran_out.remove(colour);
+            } else if previous[colour] > 0 {
+                ran_out.insert(colour.clone(), i + 1);
+            }
+        }
+
+        timeline.push(Level {
+            entry: i + 1,
+            time: entry.time,
+            total: inventory.total(),
+            colours: levels.clone(),
+        });
+        previous = levels;
+    }
+
+    let colours = previous
+        .into_iter()
+        .map(|(colour, left)| Depletion {
+            stocked: stocked.get(&colour).copied().unwrap_or(0),
+            given: given.get(&colour).copied().unwrap_or(0),
+            left,
+            ran_out: ran_out.get(&colour).copied(),
+            colour,
+        })
+        .collect();
+
+    Report {
+        summary,
+        colours,
+        timeline,
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Section {
+    Summary,
+    Colours,
+    Timeline,
+}
+
+impl Section {
+    pub const ALL: [Section; 3] = [Section::Summary, Section::Colours, Section::Timeline];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Section::Summary => "summary",
+            Section::Colours => "colours",
+            Section::Timeline => "timeline",
+        }
+    }
+}
+
+impl FromStr for Section {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Section, String> {
+        Section::ALL
+            .into_iter()
+            .find(|section| section.name() == text)
+            .ok_or_else(|| {
+                format!("unknown section {text:?}, expected summary, colours or timeline")
+            })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReportFormat {
+    // Lined up for reading in a terminal
+    Table,
+    // One CSV table per section, separated by blank lines
+    Csv,
+    // A single object with a field per section
+    Json,
+}
+
+struct Percent(f64);
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.0}%", self.0 * 100.0)
+    }
+}
+
+impl Report {
+    // The column headings and rows of one section, for the table and CSV. The
+    // table gives percentages where the CSV gives plain numbers.
+    fn rows(&self, section: Section, format: ReportFormat) -> (Vec<String>, Vec<Vec<String>>) {
+        let table = format == ReportFormat::Table;
+        let headings = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+
+        match section {
+            Section::Summary => {
+                let summary = &self.summary;
+                let rate = summary
+                    .honoured_rate()
+                    .map_or_else(String::new, |rate| format!("{rate:.3}"));
+                (
+                    headings(&[
+                        "requests", "stated", "honoured", "fallback", "refused", "rate",
+                    ]),
+                    vec![vec![
+                        summary.requests.to_string(),
+                        summary.stated.to_string(),
+                        summary.honoured.to_string(),
+                        summary.fallback.to_string(),
+                        summary.refused.to_string(),
+                        rate,
+                    ]],
+                )
+            }
+            Section::Colours => (
+                headings(&["colour", "stocked", "given", "left", "depleted", "ran out"]),
+                self.colours
+                    .iter()
+                    .map(|depletion| {
+                        vec![
+                            depletion.colour.to_string(),
+                            depletion.stocked.to_string(),
+                            depletion.given.to_string(),
+                            depletion.left.to_string(),
+                            if table {
+                                Percent(depletion.depleted()).to_string()
+                            } else {
+                                format!("{:.3}", depletion.depleted())
+                            },
+                            match depletion.ran_out {
+                                Some(entry) if table => format!("entry {entry}"),
+                                Some(entry) => entry.to_string(),
+                                None if table => String::from("-"),
+                                None => String::new(),
+                            },
+                        ]
+                    })
+                    .collect(),
+            ),
+            Section::Timeline => {
+                let mut names = vec![
+                    String::from("entry"),
+                    String::from("time"),
+                    String::from("total"),
+                ];
+                names.extend(self.colours.iter().map(|d| d.colour.to_string()));
+                let rows = self
+                    .timeline
+                    .iter()
+                    .map(|level| {
+                        let mut row = vec![
+                            level.entry.to_string(),
+                            level.time.to_string(),
+                            level.total.to_string(),
+                        ];
+                        row.extend(level.colours.values().map(u32::to_string));
+                        row
+                    })
+                    .collect();
+                (names, rows)
+            }
+        }
+    }
+
+    fn json(&self, section: Section) -> Value {
+        match section {
+            Section::Summary => {
+                let summary = &self.summary;
+                json!({
+                    "requests": summary.requests,
+                    "stated": summary.stated,
+                    "honoured": summary.honoured,
+                    "fallback": summary.fallback,
+                    "refused": summary.refused,
+                    "honoured_rate": summary.honoured_rate(),
+                })
+            }
+            Section::Colours => self
+                .colours
+                .iter()
+                .map(|depletion| {
+                    json!({
+                        "colour": depletion.colour.to_string(),
+                        "stocked": depletion.stocked,
+                        "given": depletion.given,
+                        "left": depletion.left,
+                        "depleted": depletion.depleted(),
+                        "ran_out": depletion.ran_out,
+                    })
+                })
+                .collect(),
+            Section::Timeline => self
+                .timeline
+                .iter()
+                .map(|level| {
+                    let colours: BTreeMap<String, u32> = level
+                        .colours
+                        .iter()
+                        .map(|(colour, &count)| (colour.to_string(), count))
+                        .collect();
+                    json!({
+                        "entry": level.entry,
+                        "time": level.time,
+                        "total": level.total,
+                        "colours": colours,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    fn table(&self, section: Section, out: &mut impl Write) -> io::Result<()> {
+        if section == Section::Summary {
+            let summary = &self.summary;
+            writeln!(out, "{:>5}  asked for a shirt", summary.requests)?;
+            write!(out, "{:>5}  stated a preference", summary.stated)?;
+            match summary.honoured_rate() {
+                Some(rate) => writeln!(out, ", {} honoured ({})", summary.honoured, Percent(rate))?,
+                None => writeln!(out)?,
+            }
+            writeln!(out, "{:>5}  took whatever was going", summary.fallback)?;
+            return writeln!(out, "{:>5}  went without", summary.refused);
+        }
+
+        let (headings, rows) = self.rows(section, ReportFormat::Table);
+
+        let widths: Vec<usize> = (0..headings.len())
+            .map(|column| {
+                rows.iter()
+                    .map(|row| row[column].len())
+                    .chain([headings[column].len()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        for row in [headings].iter().chain(&rows) {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(column, (cell, &width))| {
+                    // Names on the left, numbers on the right
+                    if column == 0 && section == Section::Colours {
+                        format!("{cell:<width$}")
+                    } else {
+                        format!("{cell:>width$}")
+                    }
+                })
+                .collect();
+            writeln!(out, "{}", cells.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+
+    pub fn write(
+        &self,
+        format: ReportFormat,
+        sections: &[Section],
+        mut out: impl Write,
+    ) -> io::Result<()> {
+        match format {
+            ReportFormat::Table => {
+                for (i, &section) in sections.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(out)?;
+                    }
+                    self.table(section, &mut out)?;
+                }
+            }
+            ReportFormat::Csv => {
+                for (i, &section) in sections.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(out)?;
+                    }
+                    let (headings, rows) = self.rows(section, format);
+                    let mut writer = csv::Writer::from_writer(&mut out);
+                    writer.write_record(&headings)?;
+                    for row in rows {
+                        writer.write_record(&row)?;
+                    }
+                    writer.flush()?;
+                }
+            }
+            ReportFormat::Json => {
+                let report: serde_json::Map<String, Value> = sections
+                    .iter()
+                    .map(|&section| (section.name().to_string(), self.json(section)))
+                    .collect();
+                serde_json::to_writer_pretty(&mut out, &report)?;
+                writeln!(out)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+// Shirts held for users who will be back for them. A held shirt stays in stock
+// but can't be given to anyone else until the hold is confirmed, cancelled or
+// runs out.
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use super::{GiveawayError, Preference, Shirt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reservation {
+    pub id: u64,
+    pub user: String,
+    // What they asked for, for the record when the shirt is handed over
+    pub preference: Preference,
+    pub shirt: Shirt,
+    // Seconds since the Unix epoch; the hold is over from then on
+    pub expires: u64,
+}
+
+impl Reservation {
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReservationError {
+    // Never made, already confirmed or cancelled, or run out
+    NotHeld(u64),
+    // Holds are counted in whole seconds, so anything shorter would have run
+    // out before it began
+    TooShort(Duration),
+    // Nothing the user would accept was free to hold
+    Unavailable(GiveawayError),
+    // The held shirt had gone from the stock by the time it was handed over,
+    // so the hold is dropped
+    Gone { id: u64, shirt: Shirt },
+}
+
+impl fmt::Display for ReservationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReservationError::NotHeld(id) => {
+                write!(f, "reservation {id} is not being held")
+            }
+            ReservationError::TooShort(hold) => {
+                write!(
+                    f,
+                    "a shirt can't be held for {hold:?}, only a second or more"
+                )
+            }
+            ReservationError::Unavailable(e) => e.fmt(f),
+            ReservationError::Gone { id, shirt } => {
+                write!(
+                    f,
+                    "the {shirt} shirt held by reservation {id} is no longer in stock"
+                )
+            }
+        }
+    }
+}
+
+impl From<GiveawayError> for ReservationError {
+    fn from(e: GiveawayError) -> ReservationError {
+        ReservationError::Unavailable(e)
+    }
+}
+
+impl Error for ReservationError {}
@@ -0,0 +1,440 @@
+// A small HTTP server for the giveaway, so several tablets at a booth can share
+// one inventory. Built on std's TcpListener in the style of the book's final
+// project (https://doc.rust-lang.org/book/ch21-00-final-project-a-web-server.html),
+// with connections answered by a fixed pool of threads and JSON in and out:
+//
+//     GET  /stock
+//          -> {"total": 3, "stock": [{"shirt": "red/M/crew", "count": 3, "held": 1}]}
+//     POST /giveaway {"user": "ferris", "preference": "*/M/*"}
+//          -> {"shirt": "red/M/crew"}
+//     POST /restock {"shirt": "red/M/crew", "count": 10}
+//          -> {"shirt": "red/M/crew", "count": 12}
+//     POST /reserve {"user": "ferris", "preference": "*/M/*", "minutes": 15}
+//          -> {"reservation": 1, "shirt": "red/M/crew", "expires": 1717200900}
+//     POST /confirm {"reservation": 1}
+//          -> {"user": "ferris", "shirt": "red/M/crew"}
+//     POST /cancel {"reservation": 1}
+//          -> {"cancelled": 1}
+//
+// The preference is optional, and a shirt is held for 30 minutes unless the
+// reservation says otherwise. Holds are only kept in memory, so restarting the
+// server forgets them and their shirts are free for anyone again; a shirt is
+// only recorded as given once the hold is confirmed. Errors come back as
+// {"error": "..."}.
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::background::Pool;
+
+use super::ledger::{Entry, Event};
+use super::shared::SharedInventory;
+use super::shop::Shop;
+use super::store::StoreError;
+use super::{Checkpoint, Inventory, Preference, Shirt};
+
+// Plenty for any request this server understands
+const MAX_BODY: usize = 64 * 1024;
+// For the request line and headers together
+const MAX_HEAD: u64 = 16 * 1024;
+
+// How long a shirt is held when the request doesn't say
+const DEFAULT_HOLD_MINUTES: u64 = 30;
+
+// A client that stops sending part way through a request gives up its turn
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// How many connections are answered at once. The rest wait for a free thread,
+// rather than piling up more threads behind the inventory's lock.
+const THREADS: usize = 8;
+
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Response {
+        Response { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Response {
+        Response {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            413 => "Content Too Large",
+            431 => "Request Header Fields Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+
+    fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        let body = self.body.to_string();
+        write!(
+            out,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            self.status,
+            self.reason(),
+            body.len()
+        )?;
+        out.flush()
+    }
+}
+
+pub struct Request {
+    pub method: String,
+    // Without any query string
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+// Reads one request, or the response to send back if it can't be understood
+fn read_request(reader: &mut impl BufRead) -> io::Result<Result<Request, Response>> {
+    // A client can't keep the server reading one endless line, or endless headers
+    let mut head = Read::take(&mut *reader, MAX_HEAD);
+    let too_large = || Response::error(431, "request line and headers too large");
+
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    if head.limit() == 0 {
+        return Ok(Err(too_large()));
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(Err(Response::error(400, "malformed request line")));
+    };
+    let method = method.to_string();
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if head.read_line(&mut header)? == 0 {
+            return Ok(Err(Response::error(400, "request ended in the headers")));
+        }
+        if head.limit() == 0 {
+            return Ok(Err(too_large()));
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            match value.trim().parse() {
+                Ok(length) => content_length = length,
+                Err(_) => return Ok(Err(Response::error(400, "bad Content-Length"))),
+            }
+        }
+    }
+
+    if content_length > MAX_BODY {
+        return Ok(Err(Response::error(413, "request body too large")));
+    }
+
+    let mut body = vec![0; content_length];
+    head.into_inner().read_exact(&mut body)?;
+
+    Ok(Ok(Request { method, path, body }))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GiveawayRequest {
+    user: String,
+    #[serde(default)]
+    preference: Option<Preference>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RestockRequest {
+    shirt: Shirt,
+    count: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReserveRequest {
+    user: String,
+    #[serde(default)]
+    preference: Option<Preference>,
+    // How long to hold the shirt for
+    #[serde(default)]
+    minutes: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReservationRequest {
+    reservation: u64,
+}
+
+fn parse<'a, T: Deserialize<'a>>(body: &'a [u8]) -> Result<T, Response> {
+    serde_json::from_slice(body).map_err(|e| Response::error(400, e.to_string()))
+}
+
+// Cheap to clone: every clone serves the same inventory
+#[derive(Clone)]
+pub struct Server {
+    inventory: SharedInventory,
+    // Where changes are saved and recorded, if anywhere
+    shop: Option<Arc<Shop>>,
+}
+
+// Saves and records a change already made to the inventory, while the caller
+// still holds the lock, so the ledger is in the same order as the changes. A
+// change that can't be saved is undone, going back to the inventory as it was
+// `before`, so that what is served never gets ahead of the stock file.
+fn record(
+    shop: Option<&Shop>,
+    opening: Option<Entry>,
+    event: Event,
+    inventory: &mut Inventory,
+    before: Checkpoint,
+) -> Result<(), Response> {
+    let Some(shop) = shop else {
+        return Ok(());
+    };
+
+    shop.record(opening, event, inventory).map_err(|e| {
+        inventory.restore(before);
+        Response::error(500, format!("the change could not be saved: {e}"))
+    })
+}
+
+impl Server {
+    pub fn new(inventory: impl Into<SharedInventory>) -> Server {
+        Server {
+            inventory: inventory.into(),
+            shop: None,
+        }
+    }
+
+    // Saves every change to the shop's stock file and records it in its ledger
+    pub fn keeping(shop: Shop) -> Result<Server, StoreError> {
+        Ok(Server {
+            inventory: SharedInventory::new(shop.load()?),
+            shop: Some(Arc::new(shop)),
+        })
+    }
+
+    pub fn inventory(&self) -> &SharedInventory {
+        &self.inventory
+    }
+
+    // Answers the connections in turn on a pool of threads, for as long as
+    // the listener accepts them. A connection that fails before it is accepted,
+    // or one too many open files, is only reported, so the booth stays open.
+    pub fn serve(&self, listener: TcpListener) {
+        let pool = Pool::new(THREADS);
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let server = self.clone();
+                    // A client that goes away mid request is its own problem
+                    pool.run(move |_| server.answer(stream));
+                }
+                Err(e) => eprintln!("Can't accept a connection: {e}"),
+            }
+        }
+    }
+
+    fn answer(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+        let (response, refused) = match read_request(&mut BufReader::new(&stream))? {
+            Ok(request) => (self.handle(&request), false),
+            Err(response) => (response, true),
+        };
+        response.write_to(&mut stream)?;
+
+        // Closing with some of a refused request still unread would reset the
+        // connection, and the client might never see why it was refused
+        if refused {
+            stream.shutdown(Shutdown::Write)?;
+            let _ = io::copy(&mut (&stream).take(MAX_BODY as u64), &mut io::sink());
+        }
+        Ok(())
+    }
+
+    pub fn handle(&self, request: &Request) -> Response {
+        let result = match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/stock") => Ok(self.stock()),
+            ("POST", "/giveaway") => parse(&request.body).and_then(|body| self.giveaway(body)),
+            ("POST", "/restock") => parse(&request.body).and_then(|body| self.restock(body)),
+            ("POST", "/reserve") => parse(&request.body).and_then(|body| self.reserve(body)),
+            ("POST", "/confirm") => parse(&request.body).and_then(|body| self.confirm(body)),
+            ("POST", "/cancel") => parse(&request.body).and_then(|body| self.cancel(body)),
+            (_, "/stock" | "/giveaway" | "/restock" | "/reserve" | "/confirm" | "/cancel") => {
+                Err(Response::error(
+                    405,
+                    format!("{} is not allowed on {}", request.method, request.path),
+                ))
+            }
+            _ => Err(Response::error(
+                404,
+                format!("no such path {}", request.path),
+            )),
+        };
+
+        result.unwrap_or_else(|response| response)
+    }
+
+    fn stock(&self) -> Response {
+        self.inventory.with(|inventory| {
+            let stock: Vec<Value> = inventory
+                .stock_levels()
+                .map(|(shirt, count)| {
+                    json!({
+                        "shirt": shirt.to_string(),
+                        "count": count,
+                        "held": inventory.held(shirt),
+                    })
+                })
+                .collect();
+
+            Response::ok(json!({ "total": inventory.total(), "stock": stock }))
+        })
+    }
+
+    fn giveaway(&self, request: GiveawayRequest) -> Result<Response, Response> {
+        if request.user.trim().is_empty() {
+            return Err(Response::error(400, "a giveaway needs a user"));
+        }
+        let preference = request.preference.unwrap_or_default();
+        let shop = self.shop.as_deref();
+
+        self.inventory.with(|inventory| {
+            let opening = shop.and_then(|shop| shop.opening(inventory));
+            let before = inventory.checkpoint();
+            let shirt = match inventory.giveaway(&preference) {
+                Ok(shirt) => shirt,
+                Err(e) => {
+                    let event = Event::Refused {
+                        user: request.user,
+                        preference,
+                    };
+                    record(shop, opening, event, inventory, before)?;
+                    return Err(Response::error(409, e.to_string()));
+                }
+            };
+
+            let event = Event::Giveaway {
+                user: request.user,
+                preference,
+                shirt: shirt.clone(),
+                policy: inventory.policy_name().to_string(),
+            };
+            record(shop, opening, event, inventory, before)?;
+
+            Ok(Response::ok(json!({ "shirt": shirt.to_string() })))
+        })
+    }
+
+    fn restock(&self, request: RestockRequest) -> Result<Response, Response> {
+        let RestockRequest { shirt, count } = request;
+        let shop = self.shop.as_deref();
+
+        self.inventory.with(|inventory| {
+            let opening = shop.and_then(|shop| shop.opening(inventory));
+            let before = inventory.checkpoint();
+            inventory
+                .restock(shirt.clone(), count)
+                .map_err(|e| Response::error(409, e.to_string()))?;
+
+            let event = Event::Restock {
+                shirt: shirt.clone(),
+                count,
+            };
+            record(shop, opening, event, inventory, before)?;
+
+            Ok(Response::ok(json!({
+                "shirt": shirt.to_string(),
+                "count": inventory.stock(&shirt),
+            })))
+        })
+    }
+
+    fn reserve(&self, request: ReserveRequest) -> Result<Response, Response> {
+        if request.user.trim().is_empty() {
+            return Err(Response::error(400, "a reservation needs a user"));
+        }
+        if request.minutes == Some(0) {
+            return Err(Response::error(
+                400,
+                "a shirt has to be held for a minute or more",
+            ));
+        }
+        let preference = request.preference.unwrap_or_default();
+        let hold = Duration::from_secs(
+            request
+                .minutes
+                .unwrap_or(DEFAULT_HOLD_MINUTES)
+                .saturating_mul(60),
+        );
+
+        let reservation = self
+            .inventory
+            .reserve(&request.user, &preference, hold)
+            .map_err(|e| Response::error(409, e.to_string()))?;
+
+        Ok(Response::ok(json!({
+            "reservation": reservation.id,
+            "shirt": reservation.shirt.to_string(),
+            "expires": reservation.expires,
+        })))
+    }
+
+    // The shirt is handed over, so this is when the giveaway is recorded
+    fn confirm(&self, request: ReservationRequest) -> Result<Response, Response> {
+        let shop = self.shop.as_deref();
+
+        self.inventory.with(|inventory| {
+            let opening = shop.and_then(|shop| shop.opening(inventory));
+            let before = inventory.checkpoint();
+            let reservation = inventory
+                .confirm(request.reservation)
+                .map_err(|e| Response::error(409, e.to_string()))?;
+
+            let event = Event::Giveaway {
+                user: reservation.user.clone(),
+                preference: reservation.preference,
+                shirt: reservation.shirt.clone(),
+                policy: inventory.policy_name().to_string(),
+            };
+            record(shop, opening, event, inventory, before)?;
+
+            Ok(Response::ok(json!({
+                "user": reservation.user,
+                "shirt": reservation.shirt.to_string(),
+            })))
+        })
+    }
+
+    fn cancel(&self, request: ReservationRequest) -> Result<Response, Response> {
+        let reservation = self
+            .inventory
+            .cancel(request.reservation)
+            .map_err(|e| Response::error(409, e.to_string()))?;
+
+        Ok(Response::ok(json!({ "cancelled": reservation.id })))
+    }
+}
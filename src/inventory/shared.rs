@@ -0,0 +1,85 @@
+// An inventory that any number of threads can give shirts away from at once.
+// Every operation takes the lock for its whole length, so two threads can never
+// both take the last shirt.
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use super::{
+    GiveawayError, Inventory, Preference, Reservation, ReservationError, RestockError, Shirt,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct SharedInventory {
+    inner: Arc<Mutex<Inventory>>,
+}
+
+impl From<Inventory> for SharedInventory {
+    fn from(inventory: Inventory) -> SharedInventory {
+        SharedInventory {
+            inner: Arc::new(Mutex::new(inventory)),
+        }
+    }
+}
+
+impl SharedInventory {
+    pub fn new(inventory: Inventory) -> SharedInventory {
+        SharedInventory::from(inventory)
+    }
+
+    // Each Inventory method leaves the stock consistent, and `with` undoes
+    // whatever its closure did if it panics, so a thread that panicked while
+    // holding the lock can't have left it half changed
+    fn lock(&self) -> MutexGuard<'_, Inventory> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn giveaway(&self, preference: &Preference) -> Result<Shirt, GiveawayError> {
+        self.lock().giveaway(preference)
+    }
+
+    pub fn reserve(
+        &self,
+        user: &str,
+        preference: &Preference,
+        hold: Duration,
+    ) -> Result<Reservation, ReservationError> {
+        self.lock().reserve(user, preference, hold)
+    }
+
+    pub fn confirm(&self, id: u64) -> Result<Reservation, ReservationError> {
+        self.lock().confirm(id)
+    }
+
+    pub fn cancel(&self, id: u64) -> Result<Reservation, ReservationError> {
+        self.lock().cancel(id)
+    }
+
+    pub fn restock(&self, shirt: Shirt, count: u32) -> Result<(), RestockError> {
+        self.lock().restock(shirt, count)
+    }
+
+    pub fn stock(&self, shirt: &Shirt) -> u32 {
+        self.lock().stock(shirt)
+    }
+
+    pub fn total(&self) -> u32 {
+        self.lock().total()
+    }
+
+    // For anything that has to see and change the stock in one step, such as
+    // recording a giveaway in the same order it happened. If f panics, the
+    // inventory goes back to how it was before the panic carries on.
+    pub fn with<T>(&self, f: impl FnOnce(&mut Inventory) -> T) -> T {
+        let mut inventory = self.lock();
+        let before = inventory.checkpoint();
+
+        match panic::catch_unwind(AssertUnwindSafe(|| f(&mut inventory))) {
+            Ok(value) => value,
+            Err(panic) => {
+                inventory.restore(before);
+                panic::resume_unwind(panic)
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+// What a shirt is: any colour and style, in one of the usual sizes. A shirt is
+// written as colour/size/style, e.g. "red/M/crew", and a preference the same
+// way with * for anything the user doesn't mind about, e.g. "*/M/*".
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseShirtError {
+    // Not three parts separated by /
+    WrongShape(String),
+    // The colour or style was left blank
+    Blank(&'static str),
+    // A * where a shirt needs an actual colour or style
+    Wildcard(&'static str),
+    UnknownSize(String),
+}
+
+impl fmt::Display for ParseShirtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseShirtError::WrongShape(text) => {
+                write!(
+                    f,
+                    "expected colour/size/style, e.g. red/M/crew, but got {text:?}"
+                )
+            }
+            ParseShirtError::Blank(part) => write!(f, "the {part} is blank"),
+            ParseShirtError::Wildcard(part) => {
+                write!(f, "the {part} has to be given, not *, for a shirt")
+            }
+            ParseShirtError::UnknownSize(size) => {
+                write!(
+                    f,
+                    "unknown size {size:?}, expected one of XS, S, M, L, XL or XXL"
+                )
+            }
+        }
+    }
+}
+
+impl Error for ParseShirtError {}
+
+// Colours and styles are compared ignoring case and surrounding spaces, so
+// "Red" in one stock sheet and "red " in another are the same shirt
+fn normalise(text: &str, part: &'static str) -> Result<String, ParseShirtError> {
+    let text = text.trim().to_lowercase();
+    if text.is_empty() {
+        Err(ParseShirtError::Blank(part))
+    } else if text == "*" {
+        Err(ParseShirtError::Wildcard(part))
+    } else {
+        Ok(text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShirtColour(String);
+
+impl ShirtColour {
+    // Panics on a blank name; use parse for names that come from outside
+    pub fn new(name: &str) -> ShirtColour {
+        name.parse().expect("a shirt colour needs a name")
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for ShirtColour {
+    type Err = ParseShirtError;
+
+    fn from_str(name: &str) -> Result<ShirtColour, ParseShirtError> {
+        normalise(name, "colour").map(ShirtColour)
+    }
+}
+
+impl fmt::Display for ShirtColour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShirtStyle(String);
+
+impl ShirtStyle {
+    // Panics on a blank name; use parse for names that come from outside
+    pub fn new(name: &str) -> ShirtStyle {
+        name.parse().expect("a shirt style needs a name")
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for ShirtStyle {
+    type Err = ParseShirtError;
+
+    fn from_str(name: &str) -> Result<ShirtStyle, ParseShirtError> {
+        normalise(name, "style").map(ShirtStyle)
+    }
+}
+
+impl fmt::Display for ShirtStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// Declared smallest first, so sorting by size does the obvious thing
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Size {
+    XS,
+    S,
+    M,
+    L,
+    XL,
+    XXL,
+}
+
+impl Size {
+    pub const ALL: [Size; 6] = [Size::XS, Size::S, Size::M, Size::L, Size::XL, Size::XXL];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Size::XS => "XS",
+            Size::S => "S",
+            Size::M => "M",
+            Size::L => "L",
+            Size::XL => "XL",
+            Size::XXL => "XXL",
+        }
+    }
+}
+
+impl FromStr for Size {
+    type Err = ParseShirtError;
+
+    fn from_str(name: &str) -> Result<Size, ParseShirtError> {
+        let name = name.trim();
+        Size::ALL
+            .into_iter()
+            .find(|size| size.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| ParseShirtError::UnknownSize(name.to_string()))
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// One stock keeping unit: every shirt with the same colour, size and style is
+// interchangeable
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Shirt {
+    pub colour: ShirtColour,
+    pub size: Size,
+    pub style: ShirtStyle,
+}
+
+impl Shirt {
+    pub fn new(colour: &str, size: Size, style: &str) -> Shirt {
+        Shirt {
+            colour: ShirtColour::new(colour),
+            size,
+            style: ShirtStyle::new(style),
+        }
+    }
+}
+
+fn split(text: &str) -> Result<[&str; 3], ParseShirtError> {
+    let parts: Vec<&str> = text.split('/').collect();
+    parts
+        .try_into()
+        .map_err(|_| ParseShirtError::WrongShape(text.to_string()))
+}
+
+impl FromStr for Shirt {
+    type Err = ParseShirtError;
+
+    fn from_str(text: &str) -> Result<Shirt, ParseShirtError> {
+        let [colour, size, style] = split(text)?;
+        Ok(Shirt {
+            colour: colour.parse()?,
+            size: size.parse()?,
+            style: style.parse()?,
+        })
+    }
+}
+
+impl fmt::Display for Shirt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.colour, self.size, self.style)
+    }
+}
+
+// What a user asked for. Anything left as None is up to the allocation policy.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Preference {
+    pub colour: Option<ShirtColour>,
+    pub size: Option<Size>,
+    pub style: Option<ShirtStyle>,
+}
+
+impl Preference {
+    // The user doesn't mind what they get
+    pub fn any() -> Preference {
+        Preference::default()
+    }
+
+    pub fn colour(mut self, colour: &str) -> Preference {
+        self.colour = Some(ShirtColour::new(colour));
+        self
+    }
+
+    pub fn size(mut self, size: Size) -> Preference {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn style(mut self, style: &str) -> Preference {
+        self.style = Some(ShirtStyle::new(style));
+        self
+    }
+
+    pub fn is_any(&self) -> bool {
+        *self == Preference::any()
+    }
+
+    pub fn matches(&self, shirt: &Shirt) -> bool {
+        self.colour
+            .as_ref()
+            .is_none_or(|colour| *colour == shirt.colour)
+            && self.size.is_none_or(|size| size == shirt.size)
+            && self
+                .style
+                .as_ref()
+                .is_none_or(|style| *style == shirt.style)
+    }
+}
+
+// Exactly this shirt
+impl From<Shirt> for Preference {
+    fn from(shirt: Shirt) -> Preference {
+        Preference {
+            colour: Some(shirt.colour),
+            size: Some(shirt.size),
+            style: Some(shirt.style),
+        }
+    }
+}
+
+fn wildcard<T: FromStr<Err = ParseShirtError>>(part: &str) -> Result<Option<T>, ParseShirtError> {
+    match part.trim() {
+        "*" | "" => Ok(None),
+        part => part.parse().map(Some),
+    }
+}
+
+impl FromStr for Preference {
+    type Err = ParseShirtError;
+
+    fn from_str(text: &str) -> Result<Preference, ParseShirtError> {
+        let [colour, size, style] = split(text)?;
+        Ok(Preference {
+            colour: wildcard(colour)?,
+            size: wildcard(size)?,
+            style: wildcard(style)?,
+        })
+    }
+}
+
+impl fmt::Display for Preference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let part = |value: Option<String>| value.unwrap_or_else(|| String::from("*"));
+
+        write!(
+            f,
+            "{}/{}/{}",
+            part(self.colour.as_ref().map(ShirtColour::to_string)),
+            part(self.size.as_ref().map(Size::to_string)),
+            part(self.style.as_ref().map(ShirtStyle::to_string)),
+        )
+    }
+}
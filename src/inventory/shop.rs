@@ -0,0 +1,67 @@
+// Where the giveaway tool and server keep an inventory between runs: a stock
+// file with the current stock levels, and a ledger of every change to them.
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use super::Inventory;
+use super::ledger::{Entry, Event, Ledger};
+use super::store::{self, StoreError};
+
+pub struct Shop {
+    pub stock: PathBuf,
+    pub ledger: Ledger,
+}
+
+// Io errors don't say which file they were about
+fn naming(path: &Path) -> impl Fn(StoreError) -> StoreError + '_ {
+    move |e| match e {
+        StoreError::Io(e) => {
+            StoreError::Io(io::Error::new(e.kind(), format!("{}: {e}", path.display())))
+        }
+        e => e,
+    }
+}
+
+impl Shop {
+    pub fn new(stock: impl Into<PathBuf>, ledger: impl Into<PathBuf>) -> Shop {
+        Shop {
+            stock: stock.into(),
+            ledger: Ledger::new(ledger),
+        }
+    }
+
+    // Nothing has been stocked yet the first time round
+    pub fn load(&self) -> Result<Inventory, StoreError> {
+        match store::load(&self.stock) {
+            Err(StoreError::Io(e)) if e.kind() == ErrorKind::NotFound => Ok(Inventory::new()),
+            result => result.map_err(naming(&self.stock)),
+        }
+    }
+
+    pub fn entries(&self) -> Result<Vec<Entry>, StoreError> {
+        self.ledger.entries().map_err(naming(self.ledger.path()))
+    }
+
+    // A ledger started after the stock was opens with a stocktake, so that it
+    // can be replayed on its own. Taken before the stock changes.
+    pub fn opening(&self, inventory: &Inventory) -> Option<Entry> {
+        let stocked = inventory.stock_levels().next().is_some();
+        (!self.ledger.exists() && stocked).then(|| Entry::now(Event::stocktake(inventory)))
+    }
+
+    // Records what happened in the ledger, then saves the stock as it now is
+    pub fn record(
+        &self,
+        opening: Option<Entry>,
+        event: Event,
+        inventory: &Inventory,
+    ) -> Result<(), StoreError> {
+        for entry in opening.into_iter().chain([Entry::now(event)]) {
+            self.ledger
+                .append(&entry)
+                .map_err(naming(self.ledger.path()))?;
+        }
+
+        store::save(inventory, &self.stock).map_err(naming(&self.stock))
+    }
+}
@@ -0,0 +1,292 @@
+// Stock sheets: an inventory's stock levels as JSON or CSV. Event staff can
+// prepare one in a spreadsheet, and the giveaway tool keeps its stock in one
+// between runs. Either way a sheet lists each shirt with how many are left:
+//
+//     colour,size,style,count
+//     red,M,crew,10
+//     blue,XL,v-neck,4
+//
+// or in JSON
+//
+//     [{"colour": "red", "size": "M", "style": "crew", "count": 10}]
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use serde_json::value::RawValue;
+
+use super::{Inventory, Preference, Shirt, ShirtColour, ShirtStyle, Size};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl Format {
+    // Going by the file's extension
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let extension = path.extension()?.to_str()?;
+
+        if extension.eq_ignore_ascii_case("json") {
+            Some(Format::Json)
+        } else if extension.eq_ignore_ascii_case("csv") {
+            Some(Format::Csv)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    UnknownFormat(PathBuf),
+    // The sheet could be read but not understood. Lines are one based, as a
+    // spreadsheet or editor would number them.
+    Invalid { line: usize, message: String },
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "{e}"),
+            StoreError::UnknownFormat(path) => write!(
+                f,
+                "{} is not a stock sheet: expected a .json or .csv file",
+                path.display()
+            ),
+            StoreError::Invalid { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl Error for StoreError {}
+
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> StoreError {
+        StoreError::Io(e)
+    }
+}
+
+// Shirts, their parts and preferences are written in sheets and the ledger just
+// as they are on the command line
+macro_rules! as_text {
+    ($($part:ty),*) => {$(
+        impl Serialize for $part {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $part {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$part, D::Error> {
+                let text = String::deserialize(deserializer)?;
+                text.parse().map_err(de::Error::custom)
+            }
+        }
+    )*};
+}
+
+as_text!(Shirt, Preference, ShirtColour, ShirtStyle, Size);
+
+// One row of a sheet
+#[derive(Serialize, Deserialize)]
+struct Record {
+    colour: ShirtColour,
+    size: Size,
+    style: ShirtStyle,
+    count: u32,
+}
+
+impl Record {
+    fn into_stock(self) -> (Shirt, u32) {
+        let shirt = Shirt {
+            colour: self.colour,
+            size: self.size,
+            style: self.style,
+        };
+        (shirt, self.count)
+    }
+}
+
+pub(crate) fn json_error(e: serde_json::Error) -> StoreError {
+    if e.is_io() {
+        return StoreError::Io(e.into());
+    }
+
+    // serde_json puts the position at the end of its messages, but the line is
+    // reported up front here for both formats
+    let message = e.to_string();
+    let message = match message.rsplit_once(" at line ") {
+        Some((message, _)) => message.to_string(),
+        None => message,
+    };
+
+    StoreError::Invalid {
+        line: e.line(),
+        message,
+    }
+}
+
+// The line a csv record starts on, counted from its byte offset, as the csv
+// crate's own line numbers leave out blank lines, and its offsets include any
+// blank lines before the record
+fn csv_line(text: &str, position: &csv::Position) -> usize {
+    let start = (position.byte() as usize).min(text.len());
+    let record = text[start..].trim_start_matches(['\r', '\n']);
+    let before = &text[..text.len() - record.len()];
+    before.matches('\n').count() + 1
+}
+
+fn csv_error(e: csv::Error, text: &str) -> StoreError {
+    let line = e.position().map_or(0, |position| csv_line(text, position));
+
+    let message = match e.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => match err.field() {
+            Some(field) => format!("column {}: {}", field + 1, err.kind()),
+            None => err.kind().to_string(),
+        },
+        csv::ErrorKind::UnequalLengths {
+            expected_len, len, ..
+        } => format!("expected {expected_len} columns but found {len}"),
+        _ => e.to_string(),
+    };
+
+    match e.into_kind() {
+        csv::ErrorKind::Io(e) => StoreError::Io(e),
+        _ => StoreError::Invalid { line, message },
+    }
+}
+
+// Each row of the sheet, in order, with the line it starts on
+fn rows(text: &str, format: Format) -> Result<Vec<(usize, Record)>, StoreError> {
+    match format {
+        Format::Json => {
+            let rows: Vec<&RawValue> = serde_json::from_str(text).map_err(json_error)?;
+            rows.into_iter()
+                .map(|row| {
+                    // The row borrows from the text, so where it starts is a
+                    // matter of pointers
+                    let start = row.get().as_ptr() as usize - text.as_ptr() as usize;
+                    let line = text[..start].matches('\n').count() + 1;
+
+                    let record =
+                        serde_json::from_str(row.get()).map_err(|e| match json_error(e) {
+                            StoreError::Invalid {
+                                line: within,
+                                message,
+                            } => StoreError::Invalid {
+                                line: line + within - 1,
+                                message,
+                            },
+                            e => e,
+                        })?;
+                    Ok((line, record))
+                })
+                .collect()
+        }
+        Format::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(text.as_bytes());
+            let headers = reader.headers().map_err(|e| csv_error(e, text))?.clone();
+
+            reader
+                .records()
+                .map(|row| {
+                    let row = row.map_err(|e| csv_error(e, text))?;
+                    let record = row
+                        .deserialize(Some(&headers))
+                        .map_err(|e| csv_error(e, text))?;
+                    let line = row
+                        .position()
+                        .map_or(0, |position| csv_line(text, position));
+                    Ok((line, record))
+                })
+                .collect()
+        }
+    }
+}
+
+// Each shirt in the sheet with its count, in the order they are listed
+pub fn read(text: &str, format: Format) -> Result<Vec<(Shirt, u32)>, StoreError> {
+    Ok(rows(text, format)?
+        .into_iter()
+        .map(|(_, record)| record.into_stock())
+        .collect())
+}
+
+// Every shirt the inventory has stocked, including those that have run out
+pub fn write(inventory: &Inventory, format: Format, out: impl Write) -> Result<(), StoreError> {
+    let records = inventory.stock_levels().map(|(shirt, count)| Record {
+        colour: shirt.colour.clone(),
+        size: shirt.size,
+        style: shirt.style.clone(),
+        count,
+    });
+
+    match format {
+        Format::Json => {
+            let mut out = out;
+            serde_json::to_writer_pretty(&mut out, &records.collect::<Vec<_>>())
+                .map_err(json_error)?;
+            writeln!(out)?;
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            for record in records {
+                writer.serialize(record).map_err(|e| csv_error(e, ""))?;
+            }
+            writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn format_of(path: &Path) -> Result<Format, StoreError> {
+    Format::from_path(path).ok_or_else(|| StoreError::UnknownFormat(path.to_path_buf()))
+}
+
+// A new inventory, using the default allocation policy, with the stock in the
+// sheet. A shirt listed twice gets the two counts added together, which is an
+// error if there would be more than an inventory can count.
+pub fn load(path: &Path) -> Result<Inventory, StoreError> {
+    let format = format_of(path)?;
+    let text = fs::read_to_string(path)?;
+
+    let mut inventory = Inventory::new();
+    for (line, record) in rows(&text, format)? {
+        let (shirt, count) = record.into_stock();
+        inventory
+            .restock(shirt, count)
+            .map_err(|e| StoreError::Invalid {
+                line,
+                message: e.to_string(),
+            })?;
+    }
+
+    Ok(inventory)
+}
+
+// Writes the sheet alongside and then moves it into place, so a crash part
+// way through never leaves a half written sheet behind
+pub fn save(inventory: &Inventory, path: &Path) -> Result<(), StoreError> {
+    let format = format_of(path)?;
+
+    let mut partial = OsString::from(path.as_os_str());
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+
+    let mut out = BufWriter::new(File::create(&partial)?);
+    write(inventory, format, &mut out)?;
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+
+    fs::rename(&partial, path)?;
+    Ok(())
+}
@@ -0,0 +1,13 @@
+pub mod background;
+pub mod cacher;
+pub mod capture;
+pub mod combinators;
+pub mod compiler;
+pub mod explain;
+pub mod guessing_game;
+pub mod inventory;
+pub mod playground;
+pub mod quiz;
+pub mod sharing_and_ownership;
+pub mod trace;
+pub mod variants;
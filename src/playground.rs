@@ -0,0 +1,211 @@
+// Interactive "uncomment the line" mode: shows a demo with its commented-out
+// variant lines, lets them be switched on and off, and compiles the result
+// with the local toolchain after every change.
+use std::io::{self, BufRead, Write};
+
+use crate::compiler::{self, Run};
+use crate::sharing_and_ownership::Demo;
+use crate::variants::{self, Variant};
+
+const HELP: &str = "\
+Commands:
+    <line>...   toggle the marked lines with these numbers, e.g. `12` or `12 14`
+    v <n>       switch on exactly the lines of documented variant n
+    r           comment every line out again
+    q           quit";
+
+struct Playground {
+    demo: &'static Demo,
+    source: &'static str,
+    variants: Vec<&'static Variant>,
+    // Zero based indices of the lines that can be toggled, and those that are on
+    toggles: Vec<usize>,
+    enabled: Vec<usize>,
+}
+
+impl Playground {
+    fn new(demo: &'static Demo) -> Result<Playground, String> {
+        let source = variants::demo_source(demo.name)
+            .ok_or_else(|| format!("No source found for {}", demo.name))?;
+        let variants: Vec<&Variant> = variants::for_demo(demo.name).collect();
+
+        let mut toggles = Vec::new();
+        for variant in &variants {
+            toggles.extend(variant.line_indices()?);
+        }
+        toggles.sort_unstable();
+        toggles.dedup();
+
+        Ok(Playground {
+            demo,
+            source,
+            variants,
+            toggles,
+            enabled: Vec::new(),
+        })
+    }
+
+    // All or nothing, so a mistyped number doesn't leave the others half toggled
+    fn toggle(&mut self, line_numbers: &[usize]) -> Result<(), String> {
+        let index = |line_number: usize| line_number.wrapping_sub(1);
+
+        if let Some(unmarked) = line_numbers
+            .iter()
+            .find(|&&n| !self.toggles.contains(&index(n)))
+        {
+            return Err(format!("Line {unmarked} is not one of the marked lines"));
+        }
+
+        for &line_number in line_numbers {
+            match self.enabled.iter().position(|&i| i == index(line_number)) {
+                Some(position) => {
+                    self.enabled.remove(position);
+                }
+                None => self.enabled.push(index(line_number)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply(&mut self, number: usize) -> Result<(), String> {
+        let variant = number
+            .checked_sub(1)
+            .and_then(|i| self.variants.get(i))
+            .ok_or_else(|| format!("There is no variant {number}"))?;
+
+        self.enabled = variant.line_indices()?;
+        Ok(())
+    }
+
+    fn function_source(&self) -> String {
+        variants::uncomment(self.source, &self.enabled)
+    }
+
+    fn left_column(&self) -> Vec<String> {
+        self.function_source()
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let mark = if self.enabled.contains(&i) {
+                    "[x]"
+                } else if self.toggles.contains(&i) {
+                    "[ ]"
+                } else {
+                    "   "
+                };
+                format!("{mark} {:>3} {line}", i + 1)
+            })
+            .collect()
+    }
+
+    fn render(&self, run: &io::Result<Run>, out: &mut impl Write) -> io::Result<()> {
+        let left = self.left_column();
+        let right = right_column(run);
+        let width = left
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        writeln!(out, "\n== {} ({}) ==", self.demo.name, self.demo.chapter)?;
+
+        for i in 0..left.len().max(right.len()) {
+            let code = left.get(i).map(String::as_str).unwrap_or("");
+            let result = right.get(i).map(String::as_str).unwrap_or("");
+            writeln!(out, "{code:width$} | {result}")?;
+        }
+
+        if !self.variants.is_empty() {
+            writeln!(out, "\nDocumented variants:")?;
+            for (i, variant) in self.variants.iter().enumerate() {
+                writeln!(out, "  v {} {}", i + 1, variant.label)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn right_column(run: &io::Result<Run>) -> Vec<String> {
+    let run = match run {
+        Ok(run) => run,
+        Err(e) => return vec![String::from("Could not run rustc:"), e.to_string()],
+    };
+
+    let (heading, text) = match &run.output {
+        None => {
+            let codes = run.compilation.error_codes();
+            let heading = if codes.is_empty() {
+                String::from("Does not compile")
+            } else {
+                format!("Does not compile: {}", codes.join(", "))
+            };
+            (heading, run.compilation.stderr.clone())
+        }
+        Some(output) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+            (String::from("Compiles, and prints:"), text)
+        }
+    };
+
+    let mut column = vec![heading, String::new()];
+    column.extend(text.lines().map(String::from));
+    column
+}
+
+// Runs the playground for one demo until the user quits or the input ends
+pub fn play(demo: &'static Demo, input: impl BufRead, mut out: impl Write) -> io::Result<()> {
+    let mut playground = match Playground::new(demo) {
+        Ok(playground) => playground,
+        Err(e) => return writeln!(out, "{e}"),
+    };
+
+    if playground.toggles.is_empty() {
+        writeln!(out, "{} has no commented-out variants to try", demo.name)?;
+    }
+
+    let mut lines = input.lines();
+
+    loop {
+        let run = compiler::run(&variants::program(demo.name, &playground.function_source()));
+        playground.render(&run, &mut out)?;
+        writeln!(out, "\n{HELP}")?;
+
+        // Keep asking until something changes, rather than recompiling the same code
+        loop {
+            write!(out, "> ")?;
+            out.flush()?;
+
+            let Some(line) = lines.next() else {
+                return Ok(());
+            };
+            let line = line?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            let result = match words.as_slice() {
+                [] => continue,
+                ["q"] => return Ok(()),
+                ["r"] => {
+                    playground.enabled.clear();
+                    Ok(())
+                }
+                ["v", number] => match number.parse() {
+                    Ok(number) => playground.apply(number),
+                    Err(_) => Err(format!("{number} is not a variant number")),
+                },
+                words => words
+                    .iter()
+                    .map(|word| word.parse().map_err(|_| format!("Unknown command: {word}")))
+                    .collect::<Result<Vec<usize>, String>>()
+                    .and_then(|numbers| playground.toggle(&numbers)),
+            };
+
+            match result {
+                Ok(()) => break,
+                Err(e) => writeln!(out, "{e}")?,
+            }
+        }
+    }
+}
@@ -0,0 +1,322 @@
+// Borrow checker quiz built from the documented variants. Each question shows
+// a demo with one variant switched on and the explanatory comments taken out,
+// asks whether it compiles and, if not, with which error code. The answer is
+// checked by compiling the snippet, not by trusting the variants table.
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::seq::SliceRandom;
+
+use crate::compiler;
+use crate::variants::{Topic, VARIANTS, Variant};
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Score {
+    pub correct: u32,
+    pub asked: u32,
+}
+
+impl Score {
+    fn add(&mut self, other: Score) {
+        self.correct += other.correct;
+        self.asked += other.asked;
+    }
+}
+
+pub type Scores = BTreeMap<Topic, Score>;
+
+fn total(scores: &Scores) -> Score {
+    let mut total = Score::default();
+    scores.values().for_each(|score| total.add(*score));
+    total
+}
+
+// The variants to ask about, in a random order
+pub fn questions(topic: Option<Topic>, count: usize) -> Vec<&'static Variant> {
+    let mut questions: Vec<&Variant> = VARIANTS
+        .iter()
+        .filter(|variant| topic.is_none_or(|topic| variant.topic == topic))
+        .collect();
+
+    questions.shuffle(&mut rand::rng());
+    questions.truncate(count);
+    questions
+}
+
+// Drops a trailing `// ...` comment, ignoring any `//` inside a string literal
+pub fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut previous = ' ';
+
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' if previous != '\\' => in_string = !in_string,
+            '/' if !in_string && previous == '/' => return line[..i - 1].trim_end(),
+            _ => {}
+        }
+        previous = c;
+    }
+
+    line
+}
+
+// The snippet as the quiz shows it: no comments to give the answer away, and
+// the lines the variant switched on marked with >>
+fn question_listing(variant: &Variant) -> Result<Vec<String>, String> {
+    let enabled = variant.line_indices()?;
+    let source = variant.function_source()?;
+    let mut listing: Vec<String> = Vec::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let code = strip_comment(line);
+
+        if code.trim().is_empty() {
+            // Keep single blank lines between blocks, but not the gaps the comments leave
+            if line.trim().is_empty() && listing.last().is_some_and(|last| !last.trim().is_empty())
+            {
+                listing.push(String::new());
+            }
+            continue;
+        }
+
+        if code.trim_start().starts_with('}') && listing.last().is_some_and(|last| last.is_empty())
+        {
+            listing.pop();
+        }
+
+        let mark = if enabled.contains(&i) { ">>" } else { "  " };
+        listing.push(format!("{mark} {code}"));
+    }
+
+    Ok(listing)
+}
+
+fn ask<B: BufRead>(
+    prompt: &str,
+    input: &mut io::Lines<B>,
+    out: &mut impl Write,
+) -> io::Result<Option<String>> {
+    write!(out, "{prompt} ")?;
+    out.flush()?;
+
+    match input.next() {
+        Some(line) => Ok(Some(line?.trim().to_string())),
+        None => Ok(None),
+    }
+}
+
+fn is_yes(answer: &str) -> Option<bool> {
+    match answer.to_lowercase().as_str() {
+        "y" | "yes" => Some(true),
+        "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+// Accepts "E0502", "e0502" or just "502"
+pub fn normalise_code(answer: &str) -> String {
+    let digits = answer.trim_start_matches(['E', 'e']);
+    match digits.parse::<u32>() {
+        Ok(number) => format!("E{number:04}"),
+        Err(_) => answer.to_uppercase(),
+    }
+}
+
+enum Asked {
+    Answered { correct: bool },
+    // The question couldn't be put or checked, so it doesn't count either way
+    Skipped,
+    // The input ran out part way through
+    Stopped,
+}
+
+fn ask_question<B: BufRead>(
+    number: usize,
+    variant: &Variant,
+    input: &mut io::Lines<B>,
+    out: &mut impl Write,
+) -> io::Result<Asked> {
+    let listing = match question_listing(variant) {
+        Ok(listing) => listing,
+        Err(e) => {
+            writeln!(out, "Skipping {}: {e}", variant.demo)?;
+            return Ok(Asked::Skipped);
+        }
+    };
+
+    writeln!(
+        out,
+        "\nQuestion {number} ({}): {}",
+        variant.topic.name(),
+        variant.demo
+    )?;
+    writeln!(out)?;
+    for line in listing {
+        writeln!(out, "{line}")?;
+    }
+    writeln!(out)?;
+
+    let compiles = loop {
+        let Some(answer) = ask("Does this compile? [y/n]", input, out)? else {
+            return Ok(Asked::Stopped);
+        };
+        match is_yes(&answer) {
+            Some(compiles) => break compiles,
+            None => writeln!(out, "Please answer y or n")?,
+        }
+    };
+
+    let code = if compiles {
+        None
+    } else {
+        match ask("Which error code? (e.g. E0382)", input, out)? {
+            Some(answer) => Some(normalise_code(&answer)),
+            None => return Ok(Asked::Stopped),
+        }
+    };
+
+    let program = variant.program().unwrap_or_default();
+    let compilation = match compiler::check(&program) {
+        Ok(compilation) => compilation,
+        Err(e) => {
+            writeln!(
+                out,
+                "Could not run rustc to check the answer, so skipping it: {e}"
+            )?;
+            return Ok(Asked::Skipped);
+        }
+    };
+    let actual_codes = compilation.error_codes();
+
+    let correct = match (&code, compilation.success) {
+        (None, true) => true,
+        (Some(code), false) => actual_codes.contains(code),
+        _ => false,
+    };
+
+    let verdict = if correct { "Correct!" } else { "Not quite." };
+    if compilation.success {
+        writeln!(out, "{verdict} It compiles.")?;
+    } else {
+        writeln!(out, "{verdict} rustc reports {}", actual_codes.join(", "))?;
+        if let Some(message) = compilation
+            .stderr
+            .lines()
+            .find(|line| line.starts_with("error["))
+        {
+            writeln!(out, "  {message}")?;
+        }
+    }
+
+    Ok(Asked::Answered { correct })
+}
+
+// Runs a quiz session over the given questions and returns the score per
+// topic. Skipped questions aren't scored.
+pub fn run(questions: &[&Variant], input: impl BufRead, mut out: impl Write) -> io::Result<Scores> {
+    let mut input = input.lines();
+    let mut scores = Scores::new();
+
+    for (i, variant) in questions.iter().enumerate() {
+        let correct = match ask_question(i + 1, variant, &mut input, &mut out)? {
+            Asked::Answered { correct } => correct,
+            Asked::Skipped => continue,
+            Asked::Stopped => break,
+        };
+
+        let score = scores.entry(variant.topic).or_default();
+        score.asked += 1;
+        if correct {
+            score.correct += 1;
+        }
+    }
+
+    Ok(scores)
+}
+
+// Past sessions are kept one line per topic per session: seconds,topic,correct,asked
+pub fn load_history(path: &Path) -> io::Result<Scores> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Scores::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut scores = Scores::new();
+
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split(',').collect();
+        let [_, topic, correct, asked] = fields.as_slice() else {
+            continue;
+        };
+        let (Some(topic), Ok(correct), Ok(asked)) =
+            (Topic::from_name(topic), correct.parse(), asked.parse())
+        else {
+            continue;
+        };
+
+        scores
+            .entry(topic)
+            .or_default()
+            .add(Score { correct, asked });
+    }
+
+    Ok(scores)
+}
+
+pub fn save_session(path: &Path, scores: &Scores) -> io::Result<()> {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    for (topic, score) in scores {
+        writeln!(
+            file,
+            "{seconds},{},{},{}",
+            topic.name(),
+            score.correct,
+            score.asked
+        )?;
+    }
+
+    Ok(())
+}
+
+fn percent(score: Score) -> String {
+    match (score.correct * 100).checked_div(score.asked) {
+        Some(percent) => format!("{percent}%"),
+        None => String::from("-"),
+    }
+}
+
+pub fn print_summary(session: &Scores, history: &Scores, out: &mut impl Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "\n{:<16} {:>12} {:>14}",
+        "Topic", "This session", "All sessions"
+    )?;
+
+    let mut rows: Vec<(String, Score, Score)> = Topic::ALL
+        .iter()
+        .filter(|topic| session.contains_key(topic) || history.contains_key(topic))
+        .map(|topic| {
+            let this = session.get(topic).copied().unwrap_or_default();
+            let all = history.get(topic).copied().unwrap_or_default();
+            (topic.name().to_string(), this, all)
+        })
+        .collect();
+    rows.push((String::from("total"), total(session), total(history)));
+
+    for (name, this, all) in rows {
+        let this = format!("{}/{} {}", this.correct, this.asked, percent(this));
+        let all = format!("{}/{} {}", all.correct, all.asked, percent(all));
+        writeln!(out, "{name:<16} {this:>12} {all:>14}")?;
+    }
+
+    Ok(())
+}
@@ -1,3 +1,13 @@
+// Ownership and borrowing experiments, mostly based on:
+// https://doc.rust-lang.org/stable/book/ch04-00-understanding-ownership.html
+//
+// The demos keep the bindings the book uses, so the compiler's unused and
+// needless-mut warnings are part of the lesson rather than something to fix.
+#![allow(unused_variables, unused_mut, unused_assignments)]
+#![allow(clippy::ptr_arg, clippy::let_and_return)]
+
+use crate::trace::Traced;
+
 fn basic_string_moves() {
     let mut x = 5;
     println!("The value of x is: {}", x);
@@ -154,7 +164,10 @@ fn immutable_local() {
     let s = String::from("hello");
     println!("The value of s is: {}", s);
 
-    // Cannot assign a new value to an immutable variable more than once [E0384]
+    // Type mismatch [E0308]
+    // Expected: String
+    // Found: &str
+    // The types are checked before the assignment, so E0384 is never reached
     // s = "sailor";
 }
 
@@ -196,7 +209,7 @@ fn multiple_immutable_borrows_of_immutable_variable_is_ok() {
 fn cannot_borrow_immutable_local_variable_as_mutable_1() {
     let s = String::from("hello");
 
-    // Cannot borrow immutable local variable `s` as mutable
+    // Cannot borrow immutable local variable `s` as mutable [E0596]
     // let mutable_borrow = &mut s;
 }
 
@@ -246,7 +259,6 @@ fn can_only_borrow_one_mutable_reference_to_a_mutable_variable() {
 
     // is flagged as an error, with the message;
     //   cannot borrow `s1` as mutable more than once at a time [E0499]
-
 }
 
 fn cannot_modify_an_immutable_borrowed_value() {
@@ -255,7 +267,7 @@ fn cannot_modify_an_immutable_borrowed_value() {
 
     fn change(some_string: &String) {
         // If uncomment the following line, compiler error occurs here,
-        // with error message "cannot borrow immutable local variable some_string as mutable"
+        // with error message "cannot borrow immutable local variable some_string as mutable" [E0596]
         //   some_string.push_str(", world");
     }
 }
@@ -339,7 +351,7 @@ fn cannot_borrow_immutable_local_variable_as_mutable_2() {
     let t1 = &mut s1;
     println!("{t1}");
 
-    // Cannot borrow immutable local variable `t1` as mutable
+    // Cannot borrow immutable local variable `t1` as mutable [E0596]
     // let t2 = &mut t1;
 }
 
@@ -361,7 +373,7 @@ fn borrowing_combos() {
     // println!("{t1}");
     // println!("{t2}");
 
-    // (3.1) Same problem if print both values in a single statement
+    // (3.1) Same problem if print both values in a single statement [E0502]
     // println!("{t1}, {t2}");
 
     // (4) print t2 followed by t1 on separate lines
@@ -395,7 +407,7 @@ fn more_borrowing_combos() {
     // println!("{t1}");
     // println!("{t2}");
 
-    // (3.1) Same problem if print both values in a single statement
+    // (3.1) Same problem if print both values in a single statement [E0502]
     // println!("{t1}, {t2}");
 
     // (4) print t2 followed by t1 on separate lines
@@ -424,26 +436,214 @@ fn multiple_scopes() {
     println!("The value of r2 is: {}", r2);
 }
 
-fn main() {
-    //basic_string_moves();
-    // ownership_and_functions();
-    // return_values_and_scope();
-    // returning_ownership_of_parameters();
-    // string_length_with_borrow();
-    // immutable_local();
-    // mutable_local();
-    // multiple_immutable_borrows_of_mutable_variable_is_ok();
-    // multiple_immutable_borrows_of_immutable_variable_is_ok();
-    // cannot_borrow_immutable_local_variable_as_mutable_1();
-    // can_only_borrow_one_mutable_reference_to_a_mutable_variable();
-    // cannot_modify_an_immutable_borrowed_value();
-    // mixed_mutability_borrow_ok();
-    // cannot_borrow_mutable_if_already_borrowed_as_immutable_1();
-    cannot_borrow_mutable_if_already_borrowed_as_immutable_2();
-    // can_modify_a_borrowed_mutable_value();
-    // cannot_move_borrowed_mutable_reference();
-    // cannot_borrow_immutable_local_variable_as_mutable_2();
-    // borrowing_combos();
-    // more_borrowing_combos();
-    // multiple_scopes();
+// The two ownership demos again, with `Traced` standing in for String so that
+// what the comments above say happens is printed as it happens. Passing a
+// String by value is spelled out with `move_to` where the move happens, as
+// Rust doesn't announce moves. Apart from that they follow the demos line for
+// line, which tests/trace.rs checks.
+fn ownership_and_functions_traced() {
+    fn takes_ownership(some_string: Traced) {
+        println!("{some_string}");
+    }
+
+    fn makes_copy(some_integer: i32) {
+        println!("{some_integer}");
+    }
+
+    let s = Traced::new("s", "hello");
+
+    takes_ownership(s.move_to("some_string"));
+
+    let x = 5;
+
+    makes_copy(x);
+
+    println!("The value of x is: {}", x);
+}
+
+fn return_values_and_scope_traced() {
+    fn gives_ownership() -> Traced {
+        let some_string = Traced::new("some_string", "yours");
+
+        some_string
+    }
+
+    fn takes_and_gives_back(a_string: Traced) -> Traced {
+        a_string
+    }
+
+    let s1 = gives_ownership().move_to("s1");
+
+    println!("The value of s1 is: {}", s1);
+
+    let s2 = Traced::new("s2", "hello");
+
+    println!("The value of s2 is: {}", s2);
+
+    let s3 = takes_and_gives_back(s2.move_to("a_string")).move_to("s3");
+
+    println!("The value of s3 is: {}", s3);
+
+    println!("The value of s1 is: {}", s1);
+}
+
+// A demo that can be picked by name from the `rusty` launcher
+pub struct Demo {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub chapter: &'static str,
+    pub run: fn(),
+}
+
+const VARIABLES: &str = "3.1 Variables and Mutability";
+const OWNERSHIP: &str = "4.1 What Is Ownership?";
+const BORROWING: &str = "4.2 References and Borrowing";
+
+// Listed in the order the demos appear in this file
+pub const DEMOS: &[Demo] = &[
+    Demo {
+        name: "basic_string_moves",
+        summary: "Copy types stay usable after assignment, a moved String does not",
+        chapter: OWNERSHIP,
+        run: basic_string_moves,
+    },
+    Demo {
+        name: "ownership_and_functions",
+        summary: "Passing a String to a function moves it, passing an i32 copies it",
+        chapter: OWNERSHIP,
+        run: ownership_and_functions,
+    },
+    Demo {
+        name: "return_values_and_scope",
+        summary: "Returning a value moves ownership out to the caller",
+        chapter: OWNERSHIP,
+        run: return_values_and_scope,
+    },
+    Demo {
+        name: "returning_ownership_of_parameters",
+        summary: "Handing a String back in a tuple alongside its length",
+        chapter: OWNERSHIP,
+        run: returning_ownership_of_parameters,
+    },
+    Demo {
+        name: "string_length_with_borrow",
+        summary: "Borrowing with &String instead of taking ownership",
+        chapter: BORROWING,
+        run: string_length_with_borrow,
+    },
+    Demo {
+        name: "immutable_local",
+        summary: "Immutable bindings cannot be assigned twice",
+        chapter: VARIABLES,
+        run: immutable_local,
+    },
+    Demo {
+        name: "mutable_local",
+        summary: "Mutable bindings can be reassigned, but only with the same type",
+        chapter: VARIABLES,
+        run: mutable_local,
+    },
+    Demo {
+        name: "multiple_immutable_borrows_of_mutable_variable_is_ok",
+        summary: "Any number of shared borrows of a mutable variable",
+        chapter: BORROWING,
+        run: multiple_immutable_borrows_of_mutable_variable_is_ok,
+    },
+    Demo {
+        name: "multiple_immutable_borrows_of_immutable_variable_is_ok",
+        summary: "Any number of shared borrows of an immutable variable",
+        chapter: BORROWING,
+        run: multiple_immutable_borrows_of_immutable_variable_is_ok,
+    },
+    Demo {
+        name: "cannot_borrow_immutable_local_variable_as_mutable_1",
+        summary: "&mut needs the variable itself to be declared mut",
+        chapter: BORROWING,
+        run: cannot_borrow_immutable_local_variable_as_mutable_1,
+    },
+    Demo {
+        name: "mutable_borrow_of_mutable_variable",
+        summary: "A mutable borrow ends after its last use",
+        chapter: BORROWING,
+        run: mutable_borrow_of_mutable_variable,
+    },
+    Demo {
+        name: "can_only_borrow_one_mutable_reference_to_a_mutable_variable",
+        summary: "Two live mutable borrows of the same variable are rejected",
+        chapter: BORROWING,
+        run: can_only_borrow_one_mutable_reference_to_a_mutable_variable,
+    },
+    Demo {
+        name: "cannot_modify_an_immutable_borrowed_value",
+        summary: "A function taking &String cannot push onto it",
+        chapter: BORROWING,
+        run: cannot_modify_an_immutable_borrowed_value,
+    },
+    Demo {
+        name: "mixed_mutability_borrow_ok",
+        summary: "A shared borrow followed by a mutable one, without overlap",
+        chapter: BORROWING,
+        run: mixed_mutability_borrow_ok,
+    },
+    Demo {
+        name: "cannot_borrow_mutable_if_already_borrowed_as_immutable_1",
+        summary: "A mutable borrow while a shared borrow is still used",
+        chapter: BORROWING,
+        run: cannot_borrow_mutable_if_already_borrowed_as_immutable_1,
+    },
+    Demo {
+        name: "cannot_borrow_mutable_if_already_borrowed_as_immutable_2",
+        summary: "The book's r1, r2 and r3 example",
+        chapter: BORROWING,
+        run: cannot_borrow_mutable_if_already_borrowed_as_immutable_2,
+    },
+    Demo {
+        name: "can_modify_a_borrowed_mutable_value",
+        summary: "A function taking &mut String can change it",
+        chapter: BORROWING,
+        run: can_modify_a_borrowed_mutable_value,
+    },
+    Demo {
+        name: "cannot_move_borrowed_mutable_reference",
+        summary: "A value cannot be moved while it is mutably borrowed",
+        chapter: BORROWING,
+        run: cannot_move_borrowed_mutable_reference,
+    },
+    Demo {
+        name: "cannot_borrow_immutable_local_variable_as_mutable_2",
+        summary: "Reborrowing a &mut as &mut needs the reference to be mut",
+        chapter: BORROWING,
+        run: cannot_borrow_immutable_local_variable_as_mutable_2,
+    },
+    Demo {
+        name: "borrowing_combos",
+        summary: "Which orders of using a reference and its reborrow compile",
+        chapter: BORROWING,
+        run: borrowing_combos,
+    },
+    Demo {
+        name: "more_borrowing_combos",
+        summary: "borrowing_combos again, with the reborrow declared mut",
+        chapter: BORROWING,
+        run: more_borrowing_combos,
+    },
+    Demo {
+        name: "multiple_scopes",
+        summary: "A mutable borrow in an inner scope frees the variable afterwards",
+        chapter: BORROWING,
+        run: multiple_scopes,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Demo> {
+    DEMOS.iter().find(|demo| demo.name == name)
+}
+
+// The version of a demo that traces its Strings, for the demos that have one
+pub fn traced(name: &str) -> Option<fn()> {
+    match name {
+        "ownership_and_functions" => Some(ownership_and_functions_traced),
+        "return_values_and_scope" => Some(return_values_and_scope_traced),
+        _ => None,
+    }
 }
@@ -0,0 +1,140 @@
+// A String that reports on itself, for checking what the ownership demos'
+// comments say happens: when each value comes into scope, moves, is cloned,
+// borrowed and dropped, and where in the source. Rust can't see a move
+// happen, so moves are made by handing the value on with `move_to`.
+use std::cell::RefCell;
+use std::fmt;
+use std::mem;
+use std::panic::Location;
+
+thread_local! {
+    // Set while `capture` runs, otherwise the trace is printed
+    static CAPTURED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+fn trace(line: String) {
+    let printed = CAPTURED.with_borrow_mut(|captured| match captured {
+        Some(lines) => {
+            lines.push(line.clone());
+            false
+        }
+        None => true,
+    });
+    if printed {
+        println!("    ~ {line}");
+    }
+}
+
+// Runs f, returning what was traced instead of printing it
+pub fn capture(f: impl FnOnce()) -> Vec<String> {
+    let outer = CAPTURED.replace(Some(Vec::new()));
+    f();
+    CAPTURED.replace(outer).unwrap_or_default()
+}
+
+fn at(location: &Location) -> String {
+    format!("{}:{}", location.file(), location.line())
+}
+
+pub struct Traced {
+    name: String,
+    value: String,
+    // Set on the husk left behind by `move_to`, which has nothing to drop
+    moved: bool,
+}
+
+impl Traced {
+    #[track_caller]
+    pub fn new(name: &str, value: &str) -> Traced {
+        trace(format!(
+            "{name} comes into scope at {}",
+            at(Location::caller())
+        ));
+        Traced {
+            name: name.to_string(),
+            value: value.to_string(),
+            moved: false,
+        }
+    }
+
+    // Hands the value on to a new owner, such as a function's parameter or
+    // the variable a function's result is assigned to
+    #[track_caller]
+    pub fn move_to(mut self, name: &str) -> Traced {
+        trace(format!(
+            "{} moves to {name} at {}",
+            self.name,
+            at(Location::caller())
+        ));
+        self.moved = true;
+        Traced {
+            name: name.to_string(),
+            value: mem::take(&mut self.value),
+            moved: false,
+        }
+    }
+
+    #[track_caller]
+    pub fn clone_as(&self, name: &str) -> Traced {
+        trace(format!(
+            "{} is cloned into {name} at {}",
+            self.name,
+            at(Location::caller())
+        ));
+        Traced {
+            name: name.to_string(),
+            value: self.value.clone(),
+            moved: false,
+        }
+    }
+
+    // A reference to the value, as `&s` would give, but traced
+    #[track_caller]
+    pub fn lend(&self) -> &Traced {
+        trace(format!(
+            "{} is borrowed at {}",
+            self.name,
+            at(Location::caller())
+        ));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn len(&self) -> usize {
+        self.value.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+}
+
+// Printing isn't traced, so the demos can show values without cluttering the trace
+impl fmt::Display for Traced {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl fmt::Debug for Traced {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Traced")
+            .field("name", &self.name)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl Drop for Traced {
+    fn drop(&mut self) {
+        if !self.moved {
+            trace(format!(
+                "{} goes out of scope and is dropped, freeing {:?}",
+                self.name, self.value
+            ));
+        }
+    }
+}
@@ -0,0 +1,393 @@
+// The "uncomment this line" variants documented in sharing_and_ownership.rs.
+//
+// Each variant names the commented-out lines to restore in one demo. What the
+// compiler should say about the result is read from the `[E0382]` style
+// annotation in the comments beside them, with no annotation meaning it
+// compiles. The lines are found in the real source file, so if a comment is
+// edited or removed the variant stops materialising rather than quietly
+// testing something else.
+const SOURCE: &str = include_str!("sharing_and_ownership.rs");
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Expected {
+    Compiles,
+    Error(&'static str),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Topic {
+    Moves,
+    Mutability,
+    MutableBorrows,
+    Reborrows,
+    Scopes,
+}
+
+impl Topic {
+    pub const ALL: [Topic; 5] = [
+        Topic::Moves,
+        Topic::Mutability,
+        Topic::MutableBorrows,
+        Topic::Reborrows,
+        Topic::Scopes,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Topic::Moves => "moves",
+            Topic::Mutability => "mutability",
+            Topic::MutableBorrows => "mutable-borrows",
+            Topic::Reborrows => "reborrows",
+            Topic::Scopes => "scopes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Topic> {
+        Topic::ALL.into_iter().find(|topic| topic.name() == name)
+    }
+}
+
+pub struct Variant {
+    pub demo: &'static str,
+    pub topic: Topic,
+    pub label: &'static str,
+    // The commented-out lines are searched for after the first line containing
+    // this text, for demos such as borrowing_combos that repeat the same line
+    // under several numbered cases
+    pub after: Option<&'static str>,
+    // The start of each commented-out line to restore, in source order
+    pub uncomment: &'static [&'static str],
+}
+
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        demo: "basic_string_moves",
+        topic: Topic::Moves,
+        label: "use s3 after moving it into s4",
+        after: None,
+        uncomment: &[r#"println!("The value of s3 is still: {}", s3);"#],
+    },
+    Variant {
+        demo: "ownership_and_functions",
+        topic: Topic::Moves,
+        label: "use s after passing it to takes_ownership",
+        after: None,
+        uncomment: &[r#"println!("The value of s is: {}", s);"#],
+    },
+    Variant {
+        demo: "return_values_and_scope",
+        topic: Topic::Moves,
+        label: "use s2 after passing it to takes_and_gives_back",
+        after: None,
+        uncomment: &[r#"println!("The value of s2 is: {}", s2);"#],
+    },
+    Variant {
+        demo: "immutable_local",
+        topic: Topic::Mutability,
+        label: "assign to the immutable v",
+        after: None,
+        uncomment: &["v = 20;"],
+    },
+    Variant {
+        demo: "immutable_local",
+        topic: Topic::Mutability,
+        label: "assign a &str to the immutable String s",
+        after: None,
+        uncomment: &[r#"s = "sailor";"#],
+    },
+    Variant {
+        demo: "mutable_local",
+        topic: Topic::Mutability,
+        label: "assign a &str to the mutable String s",
+        after: None,
+        uncomment: &[r#"s = "sailor";"#],
+    },
+    Variant {
+        demo: "cannot_borrow_immutable_local_variable_as_mutable_1",
+        topic: Topic::MutableBorrows,
+        label: "borrow the immutable s as mutable",
+        after: None,
+        uncomment: &["let mutable_borrow = &mut s;"],
+    },
+    Variant {
+        demo: "mutable_borrow_of_mutable_variable",
+        topic: Topic::MutableBorrows,
+        label: "use mutable_borrow after printing s",
+        after: None,
+        uncomment: &[r#"println!("The value of mutable_borrow is now {}", mutable_borrow);"#],
+    },
+    Variant {
+        demo: "can_only_borrow_one_mutable_reference_to_a_mutable_variable",
+        topic: Topic::MutableBorrows,
+        label: "use t1 after t2 has borrowed s1 as well",
+        after: None,
+        uncomment: &[r#"println!("{t1}");"#],
+    },
+    Variant {
+        demo: "cannot_modify_an_immutable_borrowed_value",
+        topic: Topic::MutableBorrows,
+        label: "push onto a &String",
+        after: None,
+        uncomment: &[r#"some_string.push_str(", world");"#],
+    },
+    Variant {
+        demo: "cannot_borrow_mutable_if_already_borrowed_as_immutable_1",
+        topic: Topic::MutableBorrows,
+        label: "use immutable_borrow after the mutable borrow",
+        after: None,
+        uncomment: &[r#"println!("The value of immutable_borrow is {immutable_borrow}");"#],
+    },
+    Variant {
+        demo: "cannot_borrow_mutable_if_already_borrowed_as_immutable_2",
+        topic: Topic::MutableBorrows,
+        label: "take r3 while r1 and r2 are still used",
+        after: None,
+        uncomment: &["let r3 = &mut s;", r#"println!("{r1}, {r2}, and {r3}");"#],
+    },
+    Variant {
+        demo: "cannot_move_borrowed_mutable_reference",
+        topic: Topic::Moves,
+        label: "use t1 after moving s1 into t2",
+        after: None,
+        uncomment: &[r#"println!("{t1}, {t2}")"#],
+    },
+    Variant {
+        demo: "cannot_borrow_immutable_local_variable_as_mutable_2",
+        topic: Topic::Reborrows,
+        label: "borrow the immutable t1 as mutable",
+        after: None,
+        uncomment: &["let t2 = &mut t1;"],
+    },
+    Variant {
+        demo: "borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(1) print t1 by itself",
+        after: Some("(1)"),
+        uncomment: &[r#"println!("{t1}");"#],
+    },
+    Variant {
+        demo: "borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(2) print t2 by itself",
+        after: Some("(2)"),
+        uncomment: &[r#"println!("{t2}");"#],
+    },
+    Variant {
+        demo: "borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(3) print t1 followed by t2",
+        after: Some("(3)"),
+        uncomment: &[r#"println!("{t1}");"#, r#"println!("{t2}");"#],
+    },
+    Variant {
+        demo: "borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(3.1) print t1 and t2 in one statement",
+        after: Some("(3.1)"),
+        uncomment: &[r#"println!("{t1}, {t2}");"#],
+    },
+    Variant {
+        demo: "borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(4) print t2 followed by t1",
+        after: Some("(4)"),
+        uncomment: &[r#"println!("{t2}");"#, r#"println!("{t1}");"#],
+    },
+    Variant {
+        demo: "borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(4.1) print t2 and t1 in one statement",
+        after: Some("(4.1)"),
+        uncomment: &[r#"println!("{t2}, {t1}");"#],
+    },
+    Variant {
+        demo: "more_borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(1) print t1 by itself",
+        after: Some("(1)"),
+        uncomment: &[r#"println!("{t1}");"#],
+    },
+    Variant {
+        demo: "more_borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(2) print t2 by itself",
+        after: Some("(2)"),
+        uncomment: &[r#"println!("{t2}");"#],
+    },
+    Variant {
+        demo: "more_borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(3) print t1 followed by t2",
+        after: Some("(3)"),
+        uncomment: &[r#"println!("{t1}");"#, r#"println!("{t2}");"#],
+    },
+    Variant {
+        demo: "more_borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(3.1) print t1 and t2 in one statement",
+        after: Some("(3.1)"),
+        uncomment: &[r#"println!("{t1}, {t2}");"#],
+    },
+    Variant {
+        demo: "more_borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(4) print t2 followed by t1",
+        after: Some("(4)"),
+        uncomment: &[r#"println!("{t2}");"#, r#"println!("{t1}");"#],
+    },
+    Variant {
+        demo: "more_borrowing_combos",
+        topic: Topic::Reborrows,
+        label: "(4.1) print t2 and t1 in one statement",
+        after: Some("(4.1)"),
+        uncomment: &[r#"println!("{t2}, {t1}");"#],
+    },
+    Variant {
+        demo: "multiple_scopes",
+        topic: Topic::Scopes,
+        label: "use r1 after its scope has ended",
+        after: None,
+        uncomment: &[r#"println!("The value of r1 and r2 are: {}, {}", r1, r2);"#],
+    },
+];
+
+// The source of a top level demo function, from its `fn` line to the closing brace
+pub fn demo_source(name: &str) -> Option<&'static str> {
+    let signature = format!("fn {name}() {{");
+    let start = SOURCE.find(&format!("\n{signature}"))? + 1;
+    let end = start + SOURCE[start..].find("\n}")? + "\n}".len();
+
+    Some(&SOURCE[start..end])
+}
+
+// Wraps a demo function in a main that calls it, ready to hand to rustc
+pub fn program(demo: &str, function_source: &str) -> String {
+    format!("{function_source}\n\nfn main() {{\n    {demo}();\n}}\n")
+}
+
+fn commented_code(line: &str) -> Option<&str> {
+    line.trim_start().strip_prefix("//").map(str::trim_start)
+}
+
+// The code in a comment's `[E0382]` style annotation
+fn annotation(line: &str) -> Option<&str> {
+    let comment = commented_code(line)?;
+    let start = comment.find("[E")? + 1;
+    let code = comment[start..].split(']').next()?;
+
+    let numbered = code.len() == 5 && code[1..].bytes().all(|b| b.is_ascii_digit());
+    numbered.then_some(code)
+}
+
+// The (zero based) index and code of every annotated comment line in the source
+pub fn annotations(source: &str) -> impl Iterator<Item = (usize, &str)> {
+    source
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| Some((i, annotation(line)?)))
+}
+
+// Restores the commented-out lines at the given (zero based) line indices
+pub fn uncomment(function_source: &str, indices: &[usize]) -> String {
+    function_source
+        .lines()
+        .enumerate()
+        .map(|(i, line)| match commented_code(line) {
+            Some(code) if indices.contains(&i) => {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                format!("{indent}{code}")
+            }
+            _ => line.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// The variants documented for one demo, in the order they are listed above
+pub fn for_demo(demo: &str) -> impl Iterator<Item = &'static Variant> + '_ {
+    VARIANTS.iter().filter(move |variant| variant.demo == demo)
+}
+
+impl Variant {
+    // The (zero based) indices of the lines this variant uncomments, within
+    // the demo's function source
+    pub fn line_indices(&self) -> Result<Vec<usize>, String> {
+        let source = demo_source(self.demo)
+            .ok_or_else(|| format!("No demo called {} in sharing_and_ownership.rs", self.demo))?;
+        let lines: Vec<&str> = source.lines().collect();
+
+        let mut next = match self.marker(&lines)? {
+            Some(marker) => marker + 1,
+            None => 0,
+        };
+
+        let mut indices = Vec::new();
+
+        for wanted in self.uncomment {
+            let found = (next..lines.len())
+                .find(|&i| commented_code(lines[i]).is_some_and(|code| code.starts_with(wanted)))
+                .ok_or_else(|| format!("No commented-out `{wanted}` in {}", self.demo))?;
+
+            indices.push(found);
+            next = found + 1;
+        }
+
+        Ok(indices)
+    }
+
+    // The index of the line containing `after`, if the variant has one
+    fn marker(&self, lines: &[&str]) -> Result<Option<usize>, String> {
+        let Some(marker) = self.after else {
+            return Ok(None);
+        };
+        lines
+            .iter()
+            .position(|line| line.contains(marker))
+            .map(Some)
+            .ok_or_else(|| format!("No line containing {marker} in {}", self.demo))
+    }
+
+    // The annotated line documenting this variant, and its code: the one
+    // nearest the first line it uncomments, looking only within its numbered
+    // case (up to the next blank line) if it has one. None if it compiles.
+    pub fn annotation(&self) -> Result<Option<(usize, &'static str)>, String> {
+        let indices = self.line_indices()?;
+        let source = demo_source(self.demo).unwrap_or_default();
+        let lines: Vec<&str> = source.lines().collect();
+
+        let case = match self.marker(&lines)? {
+            Some(start) => {
+                let end = (start..lines.len())
+                    .find(|&i| lines[i].trim().is_empty())
+                    .unwrap_or(lines.len());
+                start..end
+            }
+            None => 0..lines.len(),
+        };
+        let first = indices.first().copied().unwrap_or_default();
+
+        Ok(annotations(source)
+            .filter(|(i, _)| case.contains(i))
+            .min_by_key(|(i, _)| i.abs_diff(first)))
+    }
+
+    pub fn expected(&self) -> Result<Expected, String> {
+        Ok(match self.annotation()? {
+            Some((_, code)) => Expected::Error(code),
+            None => Expected::Compiles,
+        })
+    }
+
+    // The demo function with this variant's lines uncommented
+    pub fn function_source(&self) -> Result<String, String> {
+        let indices = self.line_indices()?;
+        let source = demo_source(self.demo).unwrap_or_default();
+
+        Ok(uncomment(source, &indices))
+    }
+
+    pub fn program(&self) -> Result<String, String> {
+        self.function_source()
+            .map(|source| program(self.demo, &source))
+    }
+}
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rusty_stuff::background::{self, Pool, TaskError};
+
+const SLOW: Duration = Duration::from_millis(200);
+
+fn slowly<T>(value: T) -> T {
+    thread::sleep(SLOW);
+    value
+}
+
+#[test]
+fn a_task_hands_back_the_closures_value() {
+    let task = background::run(|_| slowly(5));
+
+    assert_eq!(task.wait(), Ok(5));
+}
+
+#[test]
+fn polling_does_not_wait() {
+    let mut task = background::run(|_| slowly("done"));
+
+    let started = Instant::now();
+    assert!(!task.poll());
+    assert!(started.elapsed() < SLOW);
+
+    assert_eq!(task.wait(), Ok("done"));
+}
+
+#[test]
+fn waiting_can_give_up_and_try_again() {
+    let mut task = background::run(|_| slowly(5));
+
+    assert!(!task.wait_timeout(Duration::from_millis(10)));
+    assert!(task.wait_timeout(SLOW * 5));
+    assert!(task.poll());
+    assert_eq!(task.wait(), Ok(5));
+}
+
+#[test]
+fn a_cancelled_task_has_no_value() {
+    let mut task = background::run(|_| slowly(5));
+
+    task.cancel();
+
+    assert!(task.poll());
+    assert_eq!(task.wait(), Err(TaskError::Cancelled));
+}
+
+#[test]
+fn cancelling_a_finished_task_keeps_its_value() {
+    let mut task = background::run(|_| 5);
+    assert!(task.wait_timeout(SLOW));
+
+    task.cancel();
+
+    assert_eq!(task.wait(), Ok(5));
+}
+
+#[test]
+fn a_closure_can_give_up_when_cancelled() {
+    let rounds = Arc::new(AtomicU32::new(0));
+    let counted = Arc::clone(&rounds);
+    let task = background::run(move |cancel| {
+        while !cancel.is_cancelled() {
+            counted.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+
+    thread::sleep(Duration::from_millis(20));
+    let canceller = task.canceller();
+    thread::spawn(move || canceller.cancel()).join().unwrap();
+
+    assert_eq!(task.wait(), Err(TaskError::Cancelled));
+    let stopped_at = rounds.load(Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(rounds.load(Ordering::SeqCst), stopped_at);
+}
+
+#[test]
+fn a_panic_is_reported_rather_than_passed_on() {
+    let task = background::run(|_| -> u32 { panic!("no shirts") });
+
+    assert_eq!(task.wait(), Err(TaskError::Panicked));
+}
+
+#[test]
+fn a_pool_runs_closures_side_by_side() {
+    let pool = Pool::new(4);
+    assert_eq!(pool.threads(), 4);
+
+    let started = Instant::now();
+    let tasks: Vec<_> = (0..4).map(|n| pool.run(move |_| slowly(n))).collect();
+    let values: Vec<u32> = tasks.into_iter().map(|task| task.wait().unwrap()).collect();
+
+    assert_eq!(values, [0, 1, 2, 3]);
+    assert!(started.elapsed() < SLOW * 3, "took {:?}", started.elapsed());
+}
+
+#[test]
+fn a_task_cancelled_in_the_queue_never_runs() {
+    let ran = Arc::new(AtomicU32::new(0));
+    let pool = Pool::new(1);
+
+    let busy = pool.run(|_| slowly(()));
+    let queued = {
+        let ran = Arc::clone(&ran);
+        pool.run(move |_| ran.fetch_add(1, Ordering::SeqCst))
+    };
+    queued.cancel();
+
+    assert_eq!(busy.wait(), Ok(()));
+    assert_eq!(queued.wait(), Err(TaskError::Cancelled));
+    drop(pool);
+    assert_eq!(ran.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn a_pool_keeps_going_after_a_panic() {
+    let pool = Pool::new(1);
+
+    let panicked = pool.run(|_| -> u32 { panic!("no shirts") });
+    let next = pool.run(|_| 7);
+
+    assert_eq!(panicked.wait(), Err(TaskError::Panicked));
+    assert_eq!(next.wait(), Ok(7));
+}
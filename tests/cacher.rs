@@ -0,0 +1,114 @@
+use std::cell::Cell;
+use std::thread;
+use std::time::Duration;
+
+use rusty_stuff::cacher::Cacher;
+
+#[test]
+fn each_argument_is_calculated_once() {
+    let calls = Cell::new(0);
+    let mut square = Cacher::new(|n: u32| {
+        calls.set(calls.get() + 1);
+        n * n
+    });
+
+    assert_eq!(square.value(3), 9);
+    assert_eq!(square.value(4), 16);
+    assert_eq!(square.value(3), 9);
+    assert_eq!(square.value(4), 16);
+
+    assert_eq!(calls.get(), 2);
+    assert_eq!((square.hits(), square.misses()), (2, 2));
+}
+
+// The bug the book points out in its own Cacher, which kept the first value
+// whatever it was later asked for
+#[test]
+fn different_arguments_get_different_values() {
+    let mut cacher = Cacher::new(|a: u32| a);
+
+    assert_eq!(cacher.value(1), 1);
+    assert_eq!(cacher.value(2), 2);
+}
+
+#[test]
+fn works_with_any_key_and_value() {
+    let mut lengths = Cacher::new(|word: String| word.chars().count());
+
+    assert_eq!(lengths.value(String::from("crab")), 4);
+    assert_eq!(lengths.value(String::from("ferris")), 6);
+    assert_eq!(lengths.len(), 2);
+}
+
+#[test]
+fn the_least_recently_used_value_makes_room() {
+    let calls = Cell::new(0);
+    let mut double = Cacher::new(|n: u32| {
+        calls.set(calls.get() + 1);
+        n * 2
+    })
+    .capacity(2);
+
+    double.value(1);
+    double.value(2);
+    // 1 is now the more recently used of the two
+    double.value(1);
+    double.value(3);
+    assert_eq!(double.len(), 2);
+    assert_eq!(calls.get(), 3);
+
+    double.value(1);
+    assert_eq!(calls.get(), 3);
+    double.value(2);
+    assert_eq!(calls.get(), 4);
+}
+
+#[test]
+fn a_capacity_of_zero_keeps_nothing() {
+    let mut cacher = Cacher::new(|n: u32| n).capacity(0);
+
+    cacher.value(1);
+    cacher.value(1);
+
+    assert!(cacher.is_empty());
+    assert_eq!((cacher.hits(), cacher.misses()), (0, 2));
+}
+
+#[test]
+fn values_are_calculated_again_once_they_are_too_old() {
+    let calls = Cell::new(0);
+    let mut cacher = Cacher::new(|n: u32| {
+        calls.set(calls.get() + 1);
+        n
+    })
+    .time_to_live(Duration::from_millis(20));
+
+    cacher.value(1);
+    thread::sleep(Duration::from_millis(50));
+    cacher.value(1);
+
+    assert_eq!(calls.get(), 2);
+    assert_eq!(cacher.len(), 1);
+}
+
+#[test]
+fn values_are_kept_while_they_are_fresh() {
+    let mut cacher = Cacher::new(|n: u32| n).time_to_live(Duration::from_secs(3600));
+
+    cacher.value(1);
+    cacher.value(1);
+
+    assert_eq!(cacher.hits(), 1);
+}
+
+#[test]
+fn clearing_forgets_the_values_but_not_the_counts() {
+    let mut cacher = Cacher::new(|n: u32| n);
+    cacher.value(1);
+    cacher.value(1);
+
+    cacher.clear();
+    cacher.value(1);
+
+    assert_eq!((cacher.hits(), cacher.misses()), (1, 2));
+}
@@ -0,0 +1,185 @@
+use rusty_stuff::capture::{self, Capture, Event, Log};
+
+fn borrowed(name: &str, moved: Option<bool>) -> Event {
+    Event::Borrowed {
+        name: name.to_string(),
+        moved,
+    }
+}
+
+fn dropped(name: &str) -> Event {
+    Event::Dropped {
+        name: name.to_string(),
+    }
+}
+
+#[test]
+fn a_borrowing_closure_uses_the_value_in_place() {
+    let log = Log::new();
+    let list = log.probe("list", vec![1, 2, 3]);
+    list.settle();
+
+    let sum = || list.get().iter().sum::<i32>();
+    assert_eq!(sum(), 6);
+    drop(list);
+
+    let events = log.take();
+    assert_eq!(events, [borrowed("list", Some(false)), dropped("list")]);
+    assert_eq!(
+        capture::capture_of(&events, "list"),
+        Some(Capture::ByReference)
+    );
+}
+
+#[test]
+fn a_mutating_closure_borrows_mutably() {
+    let log = Log::new();
+    let mut list = log.probe("list", vec![1, 2, 3]);
+    list.settle();
+
+    let mut push = || list.get_mut().push(4);
+    push();
+
+    assert_eq!(
+        capture::capture_of(&log.take(), "list"),
+        Some(Capture::ByMutableReference)
+    );
+    assert_eq!(list.get(), &[1, 2, 3, 4]);
+}
+
+#[test]
+fn a_move_closure_takes_the_value_and_drops_it_with_itself() {
+    let log = Log::new();
+    let list = log.probe("list", vec![1, 2, 3]);
+    // A first use settles the probe too
+    list.get();
+    log.take();
+
+    let len = move || list.get().len();
+    assert_eq!(len(), 3);
+    assert_eq!(log.take(), [borrowed("list", Some(true))]);
+
+    drop(len);
+    assert_eq!(log.take(), [dropped("list")]);
+}
+
+#[test]
+fn a_move_closure_is_caught_even_if_it_is_the_first_to_use_the_probe() {
+    let log = Log::new();
+    let list = log.probe("list", vec![1, 2, 3]);
+    list.settle();
+
+    let len = move || list.get().len();
+    assert_eq!(len(), 3);
+
+    let events = log.take();
+    assert_eq!(events, [borrowed("list", Some(true))]);
+    assert_eq!(capture::capture_of(&events, "list"), Some(Capture::ByValue));
+}
+
+#[test]
+fn an_unsettled_probe_moved_before_its_first_use_is_unknown() {
+    let log = Log::new();
+    let list = log.probe("list", vec![1, 2, 3]);
+
+    let len = move || list.get().len();
+    assert_eq!(len(), 3);
+    assert_eq!(len(), 3);
+
+    let events = log.take();
+    assert_eq!(
+        events,
+        [borrowed("list", None), borrowed("list", Some(false))]
+    );
+    assert_eq!(capture::capture_of(&events, "list"), Some(Capture::Unknown));
+}
+
+#[test]
+fn using_the_value_up_is_a_capture_by_value() {
+    let log = Log::new();
+    let list = log.probe("list", vec![1, 2, 3]);
+
+    let take = || list.into_inner();
+    assert_eq!(take(), [1, 2, 3]);
+
+    let events = log.take();
+    assert_eq!(
+        events,
+        [Event::Consumed {
+            name: String::from("list")
+        }]
+    );
+    assert_eq!(capture::capture_of(&events, "list"), Some(Capture::ByValue));
+}
+
+#[test]
+fn a_clone_is_a_probe_of_its_own() {
+    let log = Log::new();
+    let list = log.probe("list", vec![1]);
+
+    let copy = list.clone();
+    assert_eq!(copy.name(), "list'");
+    drop(copy);
+
+    assert_eq!(
+        log.take(),
+        [
+            Event::Cloned {
+                name: String::from("list")
+            },
+            dropped("list'")
+        ]
+    );
+}
+
+#[test]
+fn printing_is_a_borrow() {
+    let log = Log::new();
+    let list = log.probe("list", vec![1, 2]);
+
+    list.settle();
+
+    assert_eq!(format!("{list:?}"), "[1, 2]");
+    assert_eq!(log.take(), [borrowed("list", Some(false))]);
+}
+
+#[test]
+fn the_strongest_use_decides_the_capture() {
+    let events = [
+        borrowed("list", Some(false)),
+        Event::BorrowedMut {
+            name: String::from("list"),
+            moved: Some(false),
+        },
+        borrowed("other", Some(true)),
+    ];
+
+    assert_eq!(
+        capture::capture_of(&events, "list"),
+        Some(Capture::ByMutableReference)
+    );
+    assert_eq!(
+        capture::capture_of(&events, "other"),
+        Some(Capture::ByValue)
+    );
+    assert_eq!(capture::capture_of(&events, "missing"), None);
+}
+
+#[test]
+fn events_read_as_sentences() {
+    let events = [
+        borrowed("list", Some(true)),
+        borrowed("list", None),
+        dropped("list"),
+    ];
+    let text: Vec<String> = events.iter().map(|event| event.to_string()).collect();
+
+    assert_eq!(
+        text,
+        [
+            "list borrowed after a move",
+            "list borrowed before it was settled, so maybe after a move",
+            "list dropped"
+        ]
+    );
+}
@@ -0,0 +1,158 @@
+use std::cell::{Cell, RefCell};
+
+use rusty_stuff::combinators::{and_then, compose, curry, partial, pipe, retry, tap};
+
+// The four equivalent forms from the closures chapter
+fn add_one_v1(x: u32) -> u32 {
+    x + 1
+}
+
+fn double(x: u32) -> u32 {
+    x * 2
+}
+
+#[test]
+fn every_form_of_add_one_composes_the_same_way() {
+    let add_one_v2 = |x: u32| -> u32 { x + 1 };
+    let add_one_v3 = |x| x + 1;
+    let add_one_v4 = |x| x + 1;
+
+    let fn_items = compose(add_one_v1, add_one_v1);
+    let annotated = compose(add_one_v2, add_one_v2);
+    let inferred = compose(add_one_v3, add_one_v4);
+    let mixed = compose(add_one_v1, add_one_v4);
+
+    for x in 0..100 {
+        assert_eq!(fn_items(x), x + 2);
+        assert_eq!(annotated(x), fn_items(x));
+        assert_eq!(inferred(x), fn_items(x));
+        assert_eq!(mixed(x), fn_items(x));
+    }
+}
+
+#[test]
+fn compose_runs_right_to_left_and_and_then_left_to_right() {
+    let compose = compose(double, add_one_v1);
+    let and_then = and_then(double, add_one_v1);
+
+    assert_eq!(compose(5), 12);
+    assert_eq!(and_then(5), 11);
+}
+
+#[test]
+fn and_then_is_compose_the_other_way_round() {
+    let to_text = |x: u32| x.to_string();
+
+    let composed = compose(to_text, add_one_v1);
+    let chained = and_then(add_one_v1, to_text);
+
+    for x in 0..100 {
+        assert_eq!(composed(x), chained(x));
+    }
+}
+
+#[test]
+fn composition_is_associative() {
+    let square = |x: u32| x * x;
+
+    let left = compose(compose(square, double), add_one_v1);
+    let right = compose(square, compose(double, add_one_v1));
+
+    for x in 0..100 {
+        assert_eq!(left(x), right(x));
+    }
+}
+
+#[test]
+fn pipe_runs_every_step_in_order() {
+    let add_one_v2 = |x: u32| -> u32 { x + 1 };
+    let add_one_v3 = |x| x + 1;
+    let add_one_v4 = |x| x + 1;
+
+    let add_four = pipe([
+        &add_one_v1 as &dyn Fn(u32) -> u32,
+        &add_one_v2,
+        &add_one_v3,
+        &add_one_v4,
+    ]);
+    let then_double = pipe([add_one_v1, double]);
+    let double_then = pipe([double, add_one_v1]);
+
+    assert_eq!(add_four(1), 5);
+    assert_eq!(then_double(5), and_then(add_one_v1, double)(5));
+    assert_eq!(double_then(5), 11);
+}
+
+#[test]
+fn an_empty_pipe_changes_nothing() {
+    let nothing = pipe(Vec::<fn(u32) -> u32>::new());
+
+    assert_eq!(nothing(7), 7);
+}
+
+#[test]
+fn curry_and_partial_agree_with_the_original() {
+    let add = |a: u32, b: u32| a + b;
+
+    let add_one = partial(add, 1);
+    let curried = curry(add);
+    let add_one_curried = curried(1);
+
+    for x in 0..100 {
+        assert_eq!(add_one(x), add_one_v1(x));
+        assert_eq!(add_one_curried(x), add_one_v1(x));
+        assert_eq!(curried(x)(x), add(x, x));
+    }
+}
+
+#[test]
+fn partial_works_with_arguments_that_are_not_copy() {
+    let greet = |greeting: String, name: &str| format!("{greeting}, {name}!");
+
+    let hello = partial(greet, String::from("Hello"));
+
+    assert_eq!(hello("Ferris"), "Hello, Ferris!");
+    assert_eq!(hello("Corro"), "Hello, Corro!");
+}
+
+#[test]
+fn tap_sees_each_value_without_changing_it() {
+    let seen = RefCell::new(Vec::new());
+    let record = tap(|&x: &u32| seen.borrow_mut().push(x));
+
+    let traced = pipe([&add_one_v1 as &dyn Fn(u32) -> u32, &record, &double]);
+
+    assert_eq!(traced(1), 4);
+    assert_eq!(traced(5), 12);
+    assert_eq!(*seen.borrow(), [2, 6]);
+}
+
+#[test]
+fn retry_stops_at_the_first_success() {
+    let calls = Cell::new(0);
+    let flaky = |x: u32| {
+        calls.set(calls.get() + 1);
+        if calls.get() < 3 {
+            Err("not yet")
+        } else {
+            Ok(x + 1)
+        }
+    };
+
+    assert_eq!(retry(flaky, 5)(1), Ok(2));
+    assert_eq!(calls.get(), 3);
+}
+
+#[test]
+fn retry_gives_up_with_the_last_failure() {
+    let calls = Cell::new(0);
+    let broken = |_: u32| -> Result<u32, u32> {
+        calls.set(calls.get() + 1);
+        Err(calls.get())
+    };
+
+    assert_eq!(retry(broken, 3)(1), Err(3));
+    assert_eq!(calls.get(), 3);
+    // Zero attempts still means one call
+    assert_eq!(retry(broken, 0)(1), Err(4));
+}
@@ -0,0 +1,52 @@
+// Fixtures shared by the inventory tests. Each test crate only uses some of
+// them, so the rest would be reported as dead code.
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+
+use rusty_stuff::inventory::Shirt;
+use rusty_stuff::inventory::ledger::{Entry, Event};
+
+pub fn shirt(sku: &str) -> Shirt {
+    sku.parse().unwrap()
+}
+
+pub fn stocktake(time: u64, stock: &[(&str, u32)]) -> Entry {
+    let stock: BTreeMap<Shirt, u32> = stock.iter().map(|&(sku, n)| (shirt(sku), n)).collect();
+    Entry {
+        time,
+        event: Event::Stocktake { stock },
+    }
+}
+
+pub fn restock(time: u64, sku: &str, count: u32) -> Entry {
+    Entry {
+        time,
+        event: Event::Restock {
+            shirt: shirt(sku),
+            count,
+        },
+    }
+}
+
+pub fn giveaway(time: u64, user: &str, preference: &str, sku: &str) -> Entry {
+    Entry {
+        time,
+        event: Event::Giveaway {
+            user: user.to_string(),
+            preference: preference.parse().unwrap(),
+            shirt: shirt(sku),
+            policy: String::from("most-stocked"),
+        },
+    }
+}
+
+pub fn refused(time: u64, user: &str, preference: &str) -> Entry {
+    Entry {
+        time,
+        event: Event::Refused {
+            user: user.to_string(),
+            preference: preference.parse().unwrap(),
+        },
+    }
+}
@@ -0,0 +1,126 @@
+// Materialises each "uncomment this line" variant from sharing_and_ownership.rs
+// and checks the local rustc still agrees with what the comments claim.
+use std::thread;
+
+use rusty_stuff::compiler;
+use rusty_stuff::sharing_and_ownership::DEMOS;
+use rusty_stuff::variants::{self, Expected, VARIANTS, Variant};
+
+fn outcome(expected: Expected) -> String {
+    match expected {
+        Expected::Compiles => String::from("compiles"),
+        Expected::Error(code) => format!("error[{code}]"),
+    }
+}
+
+// None if rustc agrees with the variant, otherwise a description of the mismatch
+fn verify(variant: &Variant) -> Option<String> {
+    let name = format!("{}: {}", variant.demo, variant.label);
+
+    let (program, expected) = match variant.program().and_then(|p| Ok((p, variant.expected()?))) {
+        Ok(materialised) => materialised,
+        Err(e) => return Some(format!("{name}\n  could not materialise: {e}")),
+    };
+
+    let compilation = compiler::check(&program).expect("rustc should be on the PATH");
+    let codes = compilation.error_codes();
+
+    let agrees = match expected {
+        Expected::Compiles => compilation.success,
+        Expected::Error(code) => !compilation.success && codes == [code],
+    };
+
+    if agrees {
+        None
+    } else {
+        Some(format!(
+            "{name}\n  expected {} but rustc reported {:?}\n{}",
+            outcome(expected),
+            codes,
+            compilation.stderr
+        ))
+    }
+}
+
+#[test]
+fn every_variant_produces_the_documented_outcome() {
+    // One thread per variant; each rustc run is independent
+    let handles: Vec<_> = VARIANTS
+        .iter()
+        .map(|variant| thread::spawn(move || verify(variant)))
+        .collect();
+
+    let failures: Vec<String> = handles
+        .into_iter()
+        .filter_map(|handle| handle.join().unwrap())
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} variants disagree with rustc:\n\n{}",
+        failures.len(),
+        VARIANTS.len(),
+        failures.join("\n\n")
+    );
+}
+
+#[test]
+fn every_demo_compiles_on_its_own_as_written() {
+    let handles: Vec<_> = DEMOS
+        .iter()
+        .map(|demo| {
+            thread::spawn(move || {
+                let source = variants::demo_source(demo.name)
+                    .unwrap_or_else(|| panic!("no source found for {}", demo.name));
+                let compilation = compiler::check(&variants::program(demo.name, source)).unwrap();
+                (demo.name, compilation)
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        let (name, compilation) = handle.join().unwrap();
+        assert!(
+            compilation.success,
+            "{name} does not compile:\n{}",
+            compilation.stderr
+        );
+    }
+}
+
+#[test]
+fn every_variant_names_a_registered_demo() {
+    for variant in VARIANTS {
+        assert!(
+            DEMOS.iter().any(|demo| demo.name == variant.demo),
+            "{} is not in the demo registry",
+            variant.demo
+        );
+    }
+}
+
+#[test]
+fn every_annotated_error_has_a_variant() {
+    let source = include_str!("../src/sharing_and_ownership.rs");
+    let mut covered = 0;
+
+    for demo in DEMOS {
+        let demo_source = variants::demo_source(demo.name).unwrap_or_default();
+        let documented: Vec<usize> = variants::for_demo(demo.name)
+            .filter_map(|variant| variant.annotation().unwrap().map(|(line, _)| line))
+            .collect();
+
+        for (line, code) in variants::annotations(demo_source) {
+            assert!(
+                documented.contains(&line),
+                "[{code}] in {}, line {} of the function, has no variant",
+                demo.name,
+                line + 1
+            );
+            covered += 1;
+        }
+    }
+
+    // Any left over are outside the registered demos altogether
+    assert_eq!(variants::annotations(source).count(), covered);
+}
@@ -0,0 +1,148 @@
+use rusty_stuff::explain::{self, Citation, Entry};
+
+const SHARING_AND_OWNERSHIP: &str = include_str!("../src/sharing_and_ownership.rs");
+
+// One based, as citations count them
+fn line_of(source: &str, text: &str) -> usize {
+    source.lines().position(|line| line.contains(text)).unwrap() + 1
+}
+
+fn citation(function: &str, line: usize, comment: &str) -> Citation {
+    Citation {
+        file: "src/sharing_and_ownership.rs",
+        line,
+        function: function.to_string(),
+        comment: comment.to_string(),
+    }
+}
+
+fn markdown(entries: &[Entry]) -> String {
+    let mut out = Vec::new();
+    explain::write_markdown(entries, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn cited_codes_are_bracketed_four_digit_codes() {
+    assert_eq!(
+        explain::cited_codes("// Value used after being moved [E0382]"),
+        ["E0382"]
+    );
+    assert_eq!(
+        explain::cited_codes("// [E0502] and then [E0499]"),
+        ["E0502", "E0499"]
+    );
+
+    assert!(explain::cited_codes("// see E0382").is_empty());
+    assert!(explain::cited_codes("// [E038]").is_empty());
+    assert!(explain::cited_codes("// [E03821]").is_empty());
+    assert!(explain::cited_codes("// [Exxxx]").is_empty());
+    assert!(explain::cited_codes("// [E0382").is_empty());
+}
+
+#[test]
+fn citations_say_where_each_code_is_cited() {
+    let citations = explain::citations();
+    let mismatches = &citations["E0308"];
+
+    let functions: Vec<&str> = mismatches
+        .iter()
+        .map(|citation| citation.function.as_str())
+        .collect();
+    assert_eq!(
+        functions,
+        ["immutable_local", "mutable_local", "rpl_variables::main"]
+    );
+
+    let first = &mismatches[0];
+    assert_eq!(first.file, "src/sharing_and_ownership.rs");
+    assert_eq!(
+        first.line,
+        line_of(SHARING_AND_OWNERSHIP, "// Type mismatch [E0308]")
+    );
+    assert_eq!(first.comment, "Type mismatch [E0308]");
+
+    assert_eq!(mismatches[2].file, "src/bin/rpl_variables.rs");
+}
+
+#[test]
+fn citations_cover_every_annotation_in_the_demos() {
+    let cited: usize = explain::citations()
+        .values()
+        .flatten()
+        .filter(|citation| citation.file == "src/sharing_and_ownership.rs")
+        .count();
+
+    let annotated = SHARING_AND_OWNERSHIP
+        .lines()
+        .filter(|line| !explain::cited_codes(line).is_empty())
+        .count();
+    assert_eq!(cited, annotated);
+}
+
+#[test]
+fn markdown_links_the_summary_to_a_section_per_code() {
+    let entries = [Entry {
+        code: String::from("E0382"),
+        explanation: Some(String::from(
+            "A variable was used after its contents moved.",
+        )),
+        citations: vec![
+            citation(
+                "basic_string_moves",
+                32,
+                "Value used after being moved [E0382]",
+            ),
+            citation("basic_string_moves", 40, "also [E0382]"),
+            citation("ownership_and_functions", 66, "moved | gone [E0382]"),
+        ],
+    }];
+
+    assert_eq!(
+        markdown(&entries),
+        "\
+# Error codes cited in the demos
+
+| Code | Demos |
+|------|-------|
+| [E0382](#e0382) | `basic_string_moves`, `ownership_and_functions` |
+
+## E0382
+
+[E0382 in the error index](https://doc.rust-lang.org/error_codes/E0382.html)
+
+| Demo | Line | Comment |
+|------|------|---------|
+| `basic_string_moves` | [src/sharing_and_ownership.rs:32](src/sharing_and_ownership.rs#L32) | Value used after being moved [E0382] |
+| `basic_string_moves` | [src/sharing_and_ownership.rs:40](src/sharing_and_ownership.rs#L40) | also [E0382] |
+| `ownership_and_functions` | [src/sharing_and_ownership.rs:66](src/sharing_and_ownership.rs#L66) | moved \\| gone [E0382] |
+
+A variable was used after its contents moved.
+"
+    );
+}
+
+#[test]
+fn markdown_demotes_the_explanations_headings_outside_code() {
+    let entries = [
+        Entry {
+            code: String::from("E0499"),
+            explanation: Some(String::from(
+                "# Heading\n\n```\n# fn hidden_line() {}\n```\n## Subheading",
+            )),
+            citations: vec![citation("borrowing", 1, "[E0499]")],
+        },
+        Entry {
+            code: String::from("E9999"),
+            explanation: None,
+            citations: vec![citation("borrowing", 2, "[E9999]")],
+        },
+    ];
+
+    let output = markdown(&entries);
+
+    assert!(output.contains("\n### Heading\n"));
+    assert!(output.contains("\n# fn hidden_line() {}\n"));
+    assert!(output.contains("\n#### Subheading\n"));
+    assert!(output.ends_with("rustc has no explanation for E9999.\n"));
+}
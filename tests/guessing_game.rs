@@ -0,0 +1,186 @@
+use rusty_stuff::guessing_game::{
+    self, Difficulty, Game, Outcome, Settings, parse_attempts, parse_range,
+};
+
+fn settings(difficulty: Option<Difficulty>, attempts: Option<u32>) -> Settings {
+    Settings {
+        difficulty,
+        attempts,
+        seed: 42,
+        debug: false,
+    }
+}
+
+// Plays a whole game from a script of input lines, giving back the finished
+// game and everything it wrote
+fn play(settings: Settings, script: &str) -> (Option<Game>, String) {
+    let mut out = Vec::new();
+    let game = guessing_game::run(settings, script.as_bytes(), &mut out).unwrap();
+    (game, String::from_utf8(out).unwrap())
+}
+
+// The seeded secret, found the way a player would with --debug
+fn secret(settings: Settings) -> u32 {
+    let debug = Settings {
+        debug: true,
+        ..settings
+    };
+    let (_, out) = play(debug, "");
+    out.lines()
+        .find_map(|line| line.strip_prefix("[debug] The secret number is: "))
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn guesses_are_judged_against_the_secret() {
+    let mut game = Game::with_secret(Difficulty::Normal, None, 50);
+
+    assert_eq!(game.guess("ten"), Outcome::Invalid);
+    assert_eq!(game.guess("49"), Outcome::TooSmall);
+    assert_eq!(game.guess(" 51\n"), Outcome::TooBig);
+    assert_eq!(game.guess("50"), Outcome::Win);
+    assert!(game.is_won());
+    assert_eq!(game.guessed(), 3);
+}
+
+#[test]
+fn invalid_guesses_do_not_use_up_attempts() {
+    let mut game = Game::with_secret(Difficulty::Easy, Some(2), 5);
+
+    game.guess("-1");
+    game.guess("");
+    game.guess("1");
+
+    assert_eq!(game.remaining(), Some(1));
+    assert!(!game.is_over());
+}
+
+#[test]
+fn running_out_of_attempts_loses() {
+    let mut game = Game::with_secret(Difficulty::Easy, Some(2), 5);
+
+    assert_eq!(game.guess("1"), Outcome::TooSmall);
+    assert_eq!(game.guess("9"), Outcome::TooBig);
+    assert!(game.is_lost());
+    assert!(game.is_over());
+}
+
+#[test]
+fn winning_on_the_last_attempt_is_still_a_win() {
+    let mut game = Game::with_secret(Difficulty::Easy, Some(1), 5);
+
+    assert_eq!(game.guess("5"), Outcome::Win);
+    assert!(game.is_won());
+    assert!(!game.is_lost());
+}
+
+#[test]
+#[should_panic(expected = "the game is over")]
+fn there_is_no_guessing_after_the_game_is_over() {
+    let mut game = Game::with_secret(Difficulty::Easy, None, 5);
+    game.guess("5");
+    game.guess("5");
+}
+
+#[test]
+fn a_seed_always_picks_the_same_secret_within_the_range() {
+    for seed in 0..50 {
+        let game = Game::new(Difficulty::Custom { low: 7, high: 9 }, None, seed);
+        assert!((7..=9).contains(&game.secret()));
+        assert_eq!(
+            Game::new(Difficulty::Custom { low: 7, high: 9 }, None, seed).secret(),
+            game.secret()
+        );
+    }
+}
+
+// Pinned, so a game played with --seed can be played again anywhere
+#[test]
+fn a_seed_picks_the_same_secret_everywhere() {
+    let secrets: Vec<u32> = (0..5)
+        .map(|seed| Game::new(Difficulty::Normal, None, seed).secret())
+        .collect();
+    assert_eq!(secrets, [66, 55, 20, 11, 99]);
+}
+
+#[test]
+fn ranges_and_attempts_parse() {
+    assert_eq!(
+        parse_range("5-9"),
+        Some(Difficulty::Custom { low: 5, high: 9 })
+    );
+    assert_eq!(
+        parse_range(" 3 - 3 "),
+        Some(Difficulty::Custom { low: 3, high: 3 })
+    );
+    assert_eq!(parse_range("9-5"), None);
+    assert_eq!(parse_range("9"), None);
+    assert_eq!(parse_attempts("4"), Some(4));
+    assert_eq!(parse_attempts("0"), None);
+}
+
+#[test]
+fn a_scripted_game_is_won() {
+    let settings = settings(Some(Difficulty::Normal), None);
+    let secret = secret(settings);
+
+    let (game, out) = play(settings, &format!("ten\n0\n101\n{secret}\n"));
+
+    let game = game.unwrap();
+    assert!(game.is_won());
+    assert_eq!(game.guessed(), 3);
+    assert!(!out.contains("secret"));
+    assert!(out.ends_with(&format!("You guessed: {secret}\nYou win!\n")));
+}
+
+#[test]
+fn a_scripted_game_is_lost() {
+    let (game, out) = play(settings(Some(Difficulty::Easy), Some(2)), "0\n11\n");
+
+    let game = game.unwrap();
+    assert!(game.is_lost());
+    assert!(out.contains("You have 2 guesses.\n"));
+    assert!(out.contains("Too small!\n1 guess left.\n"));
+    assert!(out.ends_with(&format!(
+        "Too big!\nYou lose! The number was {}.\n",
+        game.secret()
+    )));
+}
+
+#[test]
+fn the_menu_asks_for_whatever_the_settings_leave_open() {
+    let settings = settings(None, None);
+    let (game, out) = play(settings, "custom\n5-9\n3\n");
+
+    let game = game.unwrap();
+    assert_eq!(game.range(), (5, 9));
+    assert_eq!(game.attempts(), Some(3));
+    assert!(out.contains("I'm thinking of a number from 5 to 9.\nYou have 3 guesses.\n"));
+
+    let (game, out) = play(
+        Settings {
+            attempts: Some(1),
+            ..settings
+        },
+        "1\n",
+    );
+    assert_eq!(game.unwrap().range(), (1, 10));
+    assert!(!out.contains("How many guesses"));
+}
+
+#[test]
+fn running_out_of_input_ends_the_game() {
+    let (game, out) = play(settings(None, None), "2\n");
+    assert!(game.is_none());
+    assert!(out.ends_with("Leave blank for as many as you like\n"));
+
+    let (game, out) = play(settings(Some(Difficulty::Hard), None), "0\n");
+    let game = game.unwrap();
+    assert!(!game.is_over());
+    assert!(out.ends_with(&format!(
+        "No more guesses. The number was {}.\n",
+        game.secret()
+    )));
+}
@@ -0,0 +1,141 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rusty_stuff::guessing_game::scores::{self, HighScores, PLACES, Score};
+use rusty_stuff::guessing_game::{Difficulty, Game};
+
+// A file of its own per test, removed afterwards
+struct ScoreFile {
+    path: PathBuf,
+}
+
+impl ScoreFile {
+    fn new(name: &str) -> ScoreFile {
+        let path = env::temp_dir().join(format!(
+            "rusty-stuff-scores-{}-{name}.csv",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        ScoreFile { path }
+    }
+}
+
+impl Drop for ScoreFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn score(when: u64, difficulty: &str, guesses: u32, seconds: u64) -> Score {
+    Score {
+        when,
+        difficulty: difficulty.to_string(),
+        guesses,
+        elapsed: Duration::from_secs(seconds),
+    }
+}
+
+#[test]
+fn a_missing_file_has_no_scores() {
+    let file = ScoreFile::new("missing");
+    let high_scores = HighScores::load(&file.path).unwrap();
+
+    assert!(high_scores.table("easy").is_empty());
+    assert_eq!(high_scores.skipped(), 0);
+}
+
+#[test]
+fn saved_scores_load_back() {
+    let file = ScoreFile::new("saved");
+    let first = score(1, "easy", 3, 10);
+    let second = score(2, "5-9", 1, 2);
+
+    scores::save(&file.path, &first).unwrap();
+    scores::save(&file.path, &second).unwrap();
+
+    let high_scores = HighScores::load(&file.path).unwrap();
+    assert_eq!(high_scores.table("easy"), [&first]);
+    assert_eq!(high_scores.table("5-9"), [&second]);
+}
+
+#[test]
+fn fewer_guesses_then_less_time_then_earlier_ranks_higher() {
+    let mut high_scores = HighScores::default();
+    let later = score(5, "hard", 4, 30);
+    let earlier = score(4, "hard", 4, 30);
+    let slower = score(1, "hard", 4, 90);
+    let fewer = score(9, "hard", 3, 500);
+    for entry in [&later, &earlier, &slower, &fewer] {
+        high_scores.add(entry.clone());
+    }
+    high_scores.add(score(1, "easy", 1, 1));
+
+    assert_eq!(
+        high_scores.table("hard"),
+        [&fewer, &earlier, &later, &slower]
+    );
+    assert_eq!(high_scores.rank(&later), Some(3));
+    assert_eq!(high_scores.rank(&score(1, "hard", 1, 1)), None);
+}
+
+#[test]
+fn unreadable_lines_are_skipped_and_counted() {
+    let file = ScoreFile::new("corrupt");
+    let mut contents = b"1,easy,2,3000\ngarbage\n2,easy,x,1\n3,,1,1\n".to_vec();
+    contents.extend_from_slice(&[0xff, 0xfe, b'\n']);
+    contents.extend_from_slice(b"4,easy,3,1000\n");
+    fs::write(&file.path, contents).unwrap();
+
+    let high_scores = HighScores::load(&file.path).unwrap();
+
+    assert_eq!(high_scores.table("easy").len(), 2);
+    assert_eq!(high_scores.skipped(), 4);
+}
+
+#[test]
+fn a_score_is_not_joined_onto_a_line_cut_short() {
+    let file = ScoreFile::new("cut_short");
+    fs::write(&file.path, "1,easy,2,3000\n2,easy,4").unwrap();
+
+    scores::save(&file.path, &score(3, "easy", 1, 1)).unwrap();
+
+    let high_scores = HighScores::load(&file.path).unwrap();
+    assert_eq!(high_scores.table("easy").len(), 2);
+    assert_eq!(high_scores.skipped(), 1);
+}
+
+#[test]
+fn a_won_game_is_scored_by_its_difficulty_and_guesses() {
+    let mut game = Game::with_secret(Difficulty::Custom { low: 1, high: 20 }, None, 7);
+    game.guess("3");
+    game.guess("7");
+
+    let score = Score::of(&game);
+
+    assert_eq!(score.difficulty, "1-20");
+    assert_eq!(score.guesses, 2);
+    assert_eq!(score.elapsed, game.elapsed());
+}
+
+#[test]
+fn the_table_shows_the_top_places_and_where_the_game_placed() {
+    let mut high_scores = HighScores::default();
+    for when in 0..PLACES as u64 + 2 {
+        high_scores.add(score(when, "normal", 5, 10));
+    }
+    let latest = score(100, "normal", 6, 10);
+    high_scores.add(latest.clone());
+
+    let mut out = Vec::new();
+    high_scores.write_table(&latest, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert_eq!(
+        out.lines().filter(|line| line.ends_with("10s")).count(),
+        PLACES
+    );
+    assert!(out.contains("   ...\n    13        6      10s  <- this game\n"));
+    assert!(out.ends_with("You placed 13th of 13.\n"));
+}
@@ -0,0 +1,162 @@
+use rusty_stuff::inventory::{
+    GiveawayError, Inventory, ParseShirtError, Preference, RestockError, Shirt, Size,
+};
+
+mod common;
+
+use common::shirt;
+
+fn store() -> Inventory {
+    ["blue/M/crew", "red/M/crew", "blue/M/crew"]
+        .into_iter()
+        .map(shirt)
+        .collect()
+}
+
+#[test]
+fn giveaway_takes_the_shirt_out_of_stock() {
+    let mut store = store();
+
+    let red = Preference::any().colour("red");
+    assert_eq!(store.giveaway(&red), Ok(shirt("red/M/crew")));
+    assert_eq!(store.stock(&shirt("red/M/crew")), 0);
+    assert_eq!(store.stock(&shirt("blue/M/crew")), 2);
+}
+
+#[test]
+fn giveaway_without_a_preference_falls_back_to_the_most_stocked() {
+    let mut store = store();
+
+    assert_eq!(store.giveaway(&Preference::any()), Ok(shirt("blue/M/crew")));
+    assert_eq!(store.total(), 2);
+}
+
+#[test]
+fn giveaway_fails_when_the_preferred_colour_has_run_out() {
+    let mut store = store();
+    let red = Preference::any().colour("red");
+    store.giveaway(&red).unwrap();
+
+    assert_eq!(
+        store.giveaway(&red),
+        Err(GiveawayError::OutOfStock(red.clone()))
+    );
+    // A failed giveaway leaves the rest of the stock alone
+    assert_eq!(store.stock(&shirt("blue/M/crew")), 2);
+}
+
+#[test]
+fn giveaway_fails_once_everything_has_gone() {
+    let mut store = store();
+    for _ in 0..3 {
+        store.giveaway(&Preference::any()).unwrap();
+    }
+
+    assert_eq!(store.most_stocked(), None);
+    assert_eq!(
+        store.giveaway(&Preference::any()),
+        Err(GiveawayError::SoldOut)
+    );
+}
+
+#[test]
+fn restock_makes_a_shirt_available_again() {
+    let mut store = store();
+    let red = Preference::any().colour("red");
+    store.giveaway(&red).unwrap();
+
+    store.restock(shirt("red/M/crew"), 5).unwrap();
+
+    assert_eq!(store.stock(&shirt("red/M/crew")), 5);
+    assert_eq!(store.most_stocked(), Some(shirt("red/M/crew")));
+    assert_eq!(store.giveaway(&red), Ok(shirt("red/M/crew")));
+}
+
+#[test]
+fn restock_past_what_can_be_counted_leaves_the_stock_alone() {
+    let mut store = store();
+
+    assert_eq!(
+        store.restock(shirt("red/M/crew"), u32::MAX),
+        Err(RestockError::TooMany {
+            shirt: shirt("red/M/crew"),
+            stock: 1,
+            count: u32::MAX
+        })
+    );
+    assert_eq!(store.stock(&shirt("red/M/crew")), 1);
+}
+
+#[test]
+fn giveaway_honours_any_subset_of_the_attributes() {
+    let mut store = Inventory::new();
+    store.restock(shirt("red/S/crew"), 1).unwrap();
+    store.restock(shirt("green/M/v-neck"), 4).unwrap();
+    store.restock(shirt("green/L/crew"), 9).unwrap();
+    store.restock(shirt("blue/M/crew"), 2).unwrap();
+
+    // Any colour, size M: the most stocked of the two mediums
+    let medium = Preference::any().size(Size::M);
+    assert_eq!(store.giveaway(&medium), Ok(shirt("green/M/v-neck")));
+
+    let green_crew = Preference::any().colour("green").style("crew");
+    assert_eq!(store.giveaway(&green_crew), Ok(shirt("green/L/crew")));
+
+    let exact = Preference::from(shirt("red/S/crew"));
+    assert_eq!(store.giveaway(&exact), Ok(shirt("red/S/crew")));
+    assert_eq!(
+        store.giveaway(&exact),
+        Err(GiveawayError::OutOfStock(exact.clone()))
+    );
+}
+
+#[test]
+fn most_stocked_works_with_any_number_of_shirts() {
+    let mut store = Inventory::new();
+    for (sku, count) in [("red/S/crew", 3), ("green/XL/polo", 7), ("white/M/crew", 5)] {
+        store.restock(shirt(sku), count).unwrap();
+    }
+
+    assert_eq!(store.most_stocked(), Some(shirt("green/XL/polo")));
+}
+
+#[test]
+fn shirts_parse_ignoring_case_and_spaces() {
+    assert_eq!(
+        " Red / m / Crew".parse(),
+        Ok(Shirt::new("red", Size::M, "crew"))
+    );
+    assert_eq!(shirt("red/xxl/v-neck").to_string(), "red/XXL/v-neck");
+}
+
+#[test]
+fn bad_shirts_are_rejected() {
+    assert_eq!(
+        "red/M".parse::<Shirt>(),
+        Err(ParseShirtError::WrongShape(String::from("red/M")))
+    );
+    assert_eq!(
+        "red/XXXL/crew".parse::<Shirt>(),
+        Err(ParseShirtError::UnknownSize(String::from("XXXL")))
+    );
+    assert_eq!(
+        " /M/crew".parse::<Shirt>(),
+        Err(ParseShirtError::Blank("colour"))
+    );
+    // A shirt has to be exact, so wildcards are only for preferences
+    assert_eq!(
+        "*/M/crew".parse::<Shirt>(),
+        Err(ParseShirtError::Wildcard("colour"))
+    );
+}
+
+#[test]
+fn preferences_use_wildcards_for_anything_left_open() {
+    let preference: Preference = "*/M/*".parse().unwrap();
+
+    assert_eq!(preference, Preference::any().size(Size::M));
+    assert_eq!(preference.to_string(), "*/M/*");
+    assert!(preference.matches(&shirt("green/M/polo")));
+    assert!(!preference.matches(&shirt("green/L/polo")));
+    assert!("*/*/*".parse::<Preference>().unwrap().is_any());
+}
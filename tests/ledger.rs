@@ -0,0 +1,194 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use rusty_stuff::inventory::Inventory;
+use rusty_stuff::inventory::ledger::{self, Entry, Issue, Ledger, Until};
+use rusty_stuff::inventory::store::StoreError;
+
+mod common;
+
+use common::{giveaway, restock, shirt, stocktake};
+
+fn levels(inventory: &Inventory) -> Vec<(String, u32)> {
+    inventory
+        .stock_levels()
+        .map(|(shirt, count)| (shirt.to_string(), count))
+        .collect()
+}
+
+fn issue(entry: usize, problem: &str) -> Issue {
+    Issue {
+        entry,
+        problem: problem.to_string(),
+    }
+}
+
+// The first few minutes of an event
+fn entries() -> Vec<Entry> {
+    vec![
+        stocktake(100, &[("red/M/crew", 1), ("blue/M/crew", 2)]),
+        giveaway(160, "ferris", "red/*/*", "red/M/crew"),
+        giveaway(220, "corro", "*/*/*", "blue/M/crew"),
+        restock(300, "red/M/crew", 5),
+    ]
+}
+
+// A scratch ledger, removed afterwards
+struct TempLedger(PathBuf);
+
+impl TempLedger {
+    fn new(name: &str) -> TempLedger {
+        let path = env::temp_dir().join(format!(
+            "rusty-stuff-ledger-{}-{name}.jsonl",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        TempLedger(path)
+    }
+}
+
+impl Drop for TempLedger {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn entries_are_appended_one_per_line() {
+    let file = TempLedger::new("append");
+    let ledger = Ledger::new(&file.0);
+    assert_eq!(ledger.entries().unwrap(), []);
+
+    for entry in &entries() {
+        ledger.append(entry).unwrap();
+    }
+
+    assert_eq!(ledger.entries().unwrap(), entries());
+    let text = fs::read_to_string(&file.0).unwrap();
+    assert_eq!(text.lines().count(), 4);
+    assert_eq!(
+        text.lines().nth(1).unwrap(),
+        r#"{"time":160,"event":"giveaway","user":"ferris","preference":"red/*/*","shirt":"red/M/crew","policy":"most-stocked"}"#
+    );
+}
+
+#[test]
+fn unreadable_entries_report_their_line() {
+    let text = concat!(
+        r#"{"time":100,"event":"restock","shirt":"red/M/crew","count":1}"#,
+        "\n\n",
+        r#"{"time":160,"event":"restock","shirt":"red/XXXL/crew","count":1}"#,
+        "\n"
+    );
+
+    match ledger::read(text) {
+        Err(StoreError::Invalid { line, message }) => {
+            assert_eq!(line, 3);
+            assert!(message.starts_with("unknown size"), "{message}");
+        }
+        other => panic!("expected line 3 to be invalid, got {other:?}"),
+    }
+}
+
+#[test]
+fn replay_rebuilds_the_stock_after_any_entry() {
+    let entries = entries();
+
+    assert_eq!(levels(&ledger::replay(&entries, Until::Entry(0))), []);
+    assert_eq!(
+        levels(&ledger::replay(&entries, Until::Entry(2))),
+        [
+            (String::from("blue/M/crew"), 2),
+            (String::from("red/M/crew"), 0)
+        ]
+    );
+    assert_eq!(
+        levels(&ledger::replay(&entries, Until::End)),
+        [
+            (String::from("blue/M/crew"), 1),
+            (String::from("red/M/crew"), 5)
+        ]
+    );
+}
+
+#[test]
+fn replay_rebuilds_the_stock_at_any_time() {
+    let entries = entries();
+
+    assert_eq!(ledger::replay(&entries, Until::Time(99)).total(), 0);
+    // Everything up to and including the entry at that very second
+    assert_eq!(ledger::replay(&entries, Until::Time(220)).total(), 1);
+    assert_eq!(ledger::replay(&entries, Until::Time(299)).total(), 1);
+    assert_eq!(ledger::replay(&entries, Until::Time(u64::MAX)).total(), 6);
+}
+
+#[test]
+fn a_stocktake_replaces_everything_before_it() {
+    let entries = [
+        restock(100, "green/L/polo", 3),
+        stocktake(200, &[("red/M/crew", 2)]),
+    ];
+
+    assert_eq!(
+        levels(&ledger::replay(&entries, Until::End)),
+        [(String::from("red/M/crew"), 2)]
+    );
+}
+
+#[test]
+fn a_consistent_ledger_passes_the_audit() {
+    assert_eq!(ledger::audit(&entries()), []);
+}
+
+#[test]
+fn audit_finds_stock_given_away_that_did_not_exist() {
+    let mut entries = entries();
+    entries.push(giveaway(400, "bors", "blue/*/*", "blue/M/crew"));
+    entries.push(giveaway(460, "crab", "blue/*/*", "blue/M/crew"));
+
+    assert_eq!(
+        ledger::audit(&entries),
+        [issue(
+            6,
+            "blue/M/crew was given to crab when there were none in stock"
+        )]
+    );
+    // Replay carries on past the impossible giveaway rather than going negative
+    assert_eq!(
+        ledger::replay(&entries, Until::End).stock(&shirt("blue/M/crew")),
+        0
+    );
+}
+
+#[test]
+fn audit_finds_restocks_past_what_can_be_counted() {
+    let mut entries = entries();
+    entries.push(restock(400, "red/M/crew", u32::MAX));
+
+    assert_eq!(
+        ledger::audit(&entries),
+        [issue(
+            5,
+            "restocking 4294967295 red/M/crew would make more than 4294967295 of them"
+        )]
+    );
+}
+
+#[test]
+fn audit_finds_entries_out_of_order_and_giveaways_that_ignore_the_preference() {
+    let entries = [
+        stocktake(100, &[("red/M/crew", 1), ("blue/M/crew", 2)]),
+        giveaway(90, "ferris", "red/*/*", "blue/M/crew"),
+        giveaway(120, " ", "*/*/*", "blue/M/crew"),
+    ];
+
+    assert_eq!(
+        ledger::audit(&entries),
+        [
+            issue(2, "recorded at 90, before the entry above it at 100"),
+            issue(2, "ferris asked for red/*/* but was given blue/M/crew"),
+            issue(3, "blue/M/crew was given to nobody in particular"),
+        ]
+    );
+}
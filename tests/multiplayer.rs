@@ -0,0 +1,194 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use rusty_stuff::guessing_game::Difficulty;
+use rusty_stuff::guessing_game::multiplayer::Server;
+
+// Every round's secret is 7
+const SEVEN: Difficulty = Difficulty::Custom { low: 7, high: 7 };
+
+// Serves the game on a free port for the rest of the test run
+fn start(server: Server) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || server.serve(listener));
+    address
+}
+
+struct Client {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+impl Client {
+    // Connects without joining
+    fn connect(address: SocketAddr) -> Client {
+        let stream = TcpStream::connect(address).unwrap();
+        // A missing line fails the test rather than hanging it
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let reader = BufReader::new(stream.try_clone().unwrap());
+        Client { stream, reader }
+    }
+
+    // Connects and joins, returning the greeting
+    fn join(address: SocketAddr, name: &str) -> (Client, String) {
+        let mut client = Client::connect(address);
+        assert_eq!(
+            client.line(),
+            "Welcome to the guessing game! What's your name?"
+        );
+        client.send(name);
+        let greeting = client.line();
+        (client, greeting)
+    }
+
+    fn send(&mut self, line: &str) {
+        self.stream
+            .write_all(format!("{line}\n").as_bytes())
+            .unwrap();
+    }
+
+    fn line(&mut self) -> String {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        line.trim_end().to_string()
+    }
+}
+
+#[test]
+fn a_player_is_told_the_round_and_range() {
+    let address = start(Server::new(Difficulty::Hard, 1));
+
+    let (_, greeting) = Client::join(address, "ferris");
+
+    assert_eq!(
+        greeting,
+        "Hi ferris. Round 1: guess the number from 1 to 1000."
+    );
+}
+
+#[test]
+fn a_name_is_asked_for_again_if_blank() {
+    let address = start(Server::new(SEVEN, 1));
+    let mut client = Client::connect(address);
+    client.line();
+
+    client.send("   ");
+    assert_eq!(client.line(), "What's your name?");
+    client.send("ferris\r");
+    assert_eq!(
+        client.line(),
+        "Hi ferris. Round 1: guess the number from 7 to 7."
+    );
+}
+
+#[test]
+fn guesses_are_told_to_every_player() {
+    let address = start(Server::new(SEVEN, 1));
+    let (mut ferris, _) = Client::join(address, "ferris");
+    let (mut corro, _) = Client::join(address, "corro");
+    assert_eq!(ferris.line(), "corro joined.");
+
+    ferris.send("3");
+    assert_eq!(ferris.line(), "ferris guessed 3: Too small!");
+    assert_eq!(corro.line(), "ferris guessed 3: Too small!");
+
+    corro.send(" 9 ");
+    assert_eq!(ferris.line(), "corro guessed 9: Too big!");
+    assert_eq!(corro.line(), "corro guessed 9: Too big!");
+}
+
+#[test]
+fn only_the_guesser_hears_about_an_invalid_guess() {
+    let address = start(Server::new(SEVEN, 1));
+    let (mut ferris, _) = Client::join(address, "ferris");
+    let (mut corro, _) = Client::join(address, "corro");
+    ferris.line();
+
+    ferris.send("seven");
+    assert_eq!(ferris.line(), "Please enter a valid number!");
+
+    // corro's next line is about the next valid guess, not the invalid one
+    ferris.send("1");
+    assert_eq!(corro.line(), "ferris guessed 1: Too small!");
+}
+
+#[test]
+fn the_first_correct_guess_wins_and_starts_a_new_round() {
+    let server = Server::new(SEVEN, 1);
+    let address = start(server.clone());
+    let (mut ferris, _) = Client::join(address, "ferris");
+    let (mut corro, _) = Client::join(address, "corro");
+    ferris.line();
+
+    corro.send("7");
+    for client in [&mut ferris, &mut corro] {
+        assert_eq!(
+            client.line(),
+            "corro guessed 7: Correct! corro wins round 1."
+        );
+        assert_eq!(client.line(), "Round 2: guess the number from 7 to 7.");
+    }
+    assert_eq!(server.round(), 2);
+
+    ferris.send("7");
+    assert_eq!(
+        corro.line(),
+        "ferris guessed 7: Correct! ferris wins round 2."
+    );
+}
+
+#[test]
+fn the_same_seed_gives_the_same_rounds() {
+    let secrets = |seed| {
+        let address = start(Server::new(Difficulty::Custom { low: 1, high: 3 }, seed));
+        let (mut client, _) = Client::join(address, "ferris");
+
+        // Guessing 1, 2, 3 in turn always wins a round, and the winning
+        // guesses spell out the secrets
+        let mut secrets = Vec::new();
+        while secrets.len() < 5 {
+            for guess in ["1", "2", "3"] {
+                client.send(guess);
+                let line = client.line();
+                if line.contains("Correct!") {
+                    client.line();
+                    secrets.push(guess);
+                    break;
+                }
+            }
+        }
+        secrets
+    };
+
+    assert_eq!(secrets(99), secrets(99));
+}
+
+#[test]
+fn players_with_the_same_name_are_told_apart() {
+    let server = Server::new(SEVEN, 1);
+    let address = start(server.clone());
+    let (_first, _) = Client::join(address, "ferris");
+    let (_second, greeting) = Client::join(address, "ferris");
+
+    assert!(greeting.starts_with("Hi ferris (2)."));
+    assert_eq!(server.players(), ["ferris", "ferris (2)"]);
+}
+
+#[test]
+fn leaving_is_told_to_the_others() {
+    let server = Server::new(SEVEN, 1);
+    let address = start(server.clone());
+    let (mut ferris, _) = Client::join(address, "ferris");
+    let (corro, _) = Client::join(address, "corro");
+    ferris.line();
+
+    corro.stream.shutdown(Shutdown::Both).unwrap();
+
+    assert_eq!(ferris.line(), "corro left.");
+    assert_eq!(server.players(), ["ferris"]);
+}
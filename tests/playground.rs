@@ -0,0 +1,88 @@
+// Scripted sessions with the playground, compiling with the local rustc as a
+// real session would.
+use rusty_stuff::playground;
+use rusty_stuff::sharing_and_ownership;
+use rusty_stuff::variants;
+
+const DEMO: &str = "basic_string_moves";
+
+// Everything the playground wrote in reply to the given lines of input
+fn session(input: &str) -> String {
+    let demo = sharing_and_ownership::find(DEMO).unwrap();
+    let mut out = Vec::new();
+    playground::play(demo, input.as_bytes(), &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+// The (one based) number of the demo's first marked line, as shown on screen
+fn marked_line() -> usize {
+    let variant = variants::for_demo(DEMO).next().unwrap();
+    variant.line_indices().unwrap()[0] + 1
+}
+
+// Each time the demo was drawn, from its heading on
+fn screens(output: &str) -> Vec<&str> {
+    output.split(&format!("== {DEMO} ")).skip(1).collect()
+}
+
+#[test]
+fn the_demo_is_drawn_compiling_with_every_line_off() {
+    let output = session("q\n");
+
+    let screens = screens(&output);
+    assert_eq!(screens.len(), 1);
+    assert!(screens[0].contains("Compiles, and prints:"));
+    assert!(screens[0].contains("[ ]"));
+    assert!(!screens[0].contains("[x]"));
+    assert!(screens[0].contains("  v 1 use s3 after moving it into s4"));
+}
+
+#[test]
+fn toggling_a_marked_line_redraws_with_the_compiler_error() {
+    let output = session(&format!("{}\nq\n", marked_line()));
+
+    let screens = screens(&output);
+    assert_eq!(screens.len(), 2);
+    assert!(screens[1].contains(&format!("[x] {:>3} ", marked_line())));
+    assert!(screens[1].contains("Does not compile: E0382"));
+}
+
+#[test]
+fn a_variant_switches_on_its_lines() {
+    let output = session("v 1\nr\nq\n");
+
+    let screens = screens(&output);
+    assert_eq!(screens.len(), 3);
+    assert!(screens[1].contains("Does not compile: E0382"));
+    assert!(!screens[2].contains("[x]"));
+}
+
+#[test]
+fn an_unmarked_line_toggles_none_of_the_others() {
+    let output = session(&format!("{} 999\nq\n", marked_line()));
+
+    assert!(output.contains("Line 999 is not one of the marked lines"));
+    assert_eq!(screens(&output).len(), 1);
+
+    // Toggling the good line on its own afterwards switches it on, not off
+    let output = session(&format!("{} 999\n{}\nq\n", marked_line(), marked_line()));
+    assert!(screens(&output)[1].contains("[x]"));
+}
+
+#[test]
+fn mistakes_are_reported_without_redrawing() {
+    let output = session("toggle\nv 99\nv one\n\nq\n");
+
+    assert!(output.contains("Unknown command: toggle"));
+    assert!(output.contains("There is no variant 99"));
+    assert!(output.contains("one is not a variant number"));
+    assert_eq!(screens(&output).len(), 1);
+}
+
+#[test]
+fn the_session_ends_with_the_input() {
+    let output = session("");
+
+    assert_eq!(screens(&output).len(), 1);
+    assert!(output.ends_with("> "));
+}
@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use rusty_stuff::inventory::policy::{
+    AllocationPolicy, LeastRecentlyGiven, MostStocked, RoundRobin, TieBreak, WeightedRandom,
+};
+use rusty_stuff::inventory::{Inventory, Preference, Shirt, ShirtColour, Size};
+
+// The two shirts from the book, as colours are all these tests care about
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Colour {
+    Red,
+    Blue,
+}
+
+use Colour::{Blue, Red};
+
+impl Colour {
+    fn shirt(self) -> Shirt {
+        match self {
+            Red => Shirt::new("red", Size::M, "crew"),
+            Blue => Shirt::new("blue", Size::M, "crew"),
+        }
+    }
+
+    fn of(shirt: &Shirt) -> Colour {
+        match shirt.colour.name() {
+            "red" => Red,
+            "blue" => Blue,
+            other => panic!("unexpected colour {other}"),
+        }
+    }
+}
+
+fn stocked(policy: impl AllocationPolicy + 'static, red: u32, blue: u32) -> Inventory {
+    let mut inventory = Inventory::with_policy(policy);
+    inventory.restock(Red.shirt(), red).unwrap();
+    inventory.restock(Blue.shirt(), blue).unwrap();
+    inventory
+}
+
+fn giveaway(inventory: &mut Inventory, preference: &Preference) -> Colour {
+    Colour::of(&inventory.giveaway(preference).unwrap())
+}
+
+// Gives away `count` shirts to users with no preference
+fn give(inventory: &mut Inventory, count: usize) -> Vec<Colour> {
+    (0..count)
+        .map(|_| giveaway(inventory, &Preference::any()))
+        .collect()
+}
+
+fn tally(colours: &[Colour]) -> BTreeMap<Colour, usize> {
+    let mut tally = BTreeMap::new();
+    for &colour in colours {
+        *tally.entry(colour).or_insert(0) += 1;
+    }
+    tally
+}
+
+#[test]
+fn default_policy_is_most_stocked_with_ties_going_to_blue() {
+    let mut inventory = Inventory::new();
+    inventory.restock(Red.shirt(), 2).unwrap();
+    inventory.restock(Blue.shirt(), 2).unwrap();
+
+    assert_eq!(inventory.policy_name(), "most-stocked");
+    assert_eq!(give(&mut inventory, 4), [Blue, Red, Blue, Red]);
+}
+
+#[test]
+fn most_stocked_gives_the_shirt_there_is_most_of() {
+    let mut inventory = stocked(MostStocked::default(), 5, 2);
+
+    // Red until the counts meet, then the two colours share what is left
+    assert_eq!(give(&mut inventory, 3), [Red, Red, Red]);
+    assert_eq!(
+        tally(&give(&mut inventory, 4)),
+        BTreeMap::from([(Red, 2), (Blue, 2)])
+    );
+}
+
+#[test]
+fn most_stocked_can_prefer_red_on_a_tie() {
+    let mut inventory = stocked(
+        MostStocked::new(TieBreak::Prefer(ShirtColour::new("red"))),
+        2,
+        2,
+    );
+
+    assert_eq!(give(&mut inventory, 4), [Red, Blue, Red, Blue]);
+}
+
+#[test]
+fn most_stocked_alternating_ties_is_even_over_many_giveaways() {
+    let mut inventory = stocked(MostStocked::new(TieBreak::Alternate), 500, 500);
+
+    let given = give(&mut inventory, 1000);
+
+    assert_eq!(tally(&given), BTreeMap::from([(Red, 500), (Blue, 500)]));
+    // A tie occurs before every other giveaway, and the winner changes each time
+    let tie_winners: Vec<Colour> = given.iter().step_by(2).copied().collect();
+    assert!(tie_winners.windows(2).all(|pair| pair[0] != pair[1]));
+}
+
+#[test]
+fn round_robin_takes_turns() {
+    let mut inventory = stocked(RoundRobin::new(), 100, 100);
+
+    let given = give(&mut inventory, 200);
+
+    assert!(given.windows(2).all(|pair| pair[0] != pair[1]));
+    assert_eq!(tally(&given), BTreeMap::from([(Red, 100), (Blue, 100)]));
+}
+
+#[test]
+fn round_robin_skips_colours_that_have_run_out() {
+    let mut inventory = stocked(RoundRobin::new(), 1, 3);
+
+    // Shirts take turns in order, and blue/M/crew sorts before red/M/crew
+    assert_eq!(give(&mut inventory, 4), [Blue, Red, Blue, Blue]);
+}
+
+#[test]
+fn weighted_random_follows_the_stock_levels() {
+    // Plenty of stock, so the 3:1 ratio barely moves while 2000 are given away
+    let mut inventory = stocked(WeightedRandom::seeded(7), 300_000, 100_000);
+
+    let tally = tally(&give(&mut inventory, 2000));
+
+    let red = tally[&Red] as f64 / 2000.0;
+    assert!(
+        (0.70..0.80).contains(&red),
+        "red was given {red:.3} of the time"
+    );
+}
+
+#[test]
+fn weighted_random_never_picks_a_colour_that_has_run_out() {
+    let mut inventory = stocked(WeightedRandom::seeded(1), 0, 50);
+
+    assert!(
+        give(&mut inventory, 50)
+            .iter()
+            .all(|&colour| colour == Blue)
+    );
+}
+
+#[test]
+fn weighted_random_with_the_same_seed_repeats_itself() {
+    let mut first = stocked(WeightedRandom::seeded(42), 50, 50);
+    let mut second = stocked(WeightedRandom::seeded(42), 50, 50);
+
+    assert_eq!(give(&mut first, 100), give(&mut second, 100));
+}
+
+#[test]
+fn least_recently_given_balances_out_preferences() {
+    let mut inventory = stocked(LeastRecentlyGiven::new(), 100, 100);
+
+    // Red was just given to someone who asked for it, so Blue is next
+    giveaway(&mut inventory, &Preference::from(Red.shirt()));
+    assert_eq!(giveaway(&mut inventory, &Preference::any()), Blue);
+
+    let given = give(&mut inventory, 198);
+    assert!(given.windows(2).all(|pair| pair[0] != pair[1]));
+    assert_eq!(tally(&given), BTreeMap::from([(Red, 99), (Blue, 99)]));
+}
+
+#[test]
+fn policies_can_be_swapped_between_giveaways() {
+    let mut inventory = stocked(MostStocked::default(), 10, 1);
+    assert_eq!(giveaway(&mut inventory, &Preference::any()), Red);
+
+    inventory.set_policy(RoundRobin::new());
+    assert_eq!(inventory.policy_name(), "round-robin");
+    assert_eq!(give(&mut inventory, 2), [Blue, Red]);
+}
@@ -0,0 +1,168 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use rusty_stuff::quiz::{self, Score, Scores};
+use rusty_stuff::variants::{Topic, VARIANTS, Variant};
+
+// A history file of its own per test, removed afterwards
+struct HistoryFile {
+    path: PathBuf,
+}
+
+impl HistoryFile {
+    fn new(name: &str, contents: &str) -> HistoryFile {
+        let path = env::temp_dir().join(format!(
+            "rusty-stuff-quiz-{}-{name}.csv",
+            std::process::id()
+        ));
+        fs::write(&path, contents).unwrap();
+        HistoryFile { path }
+    }
+}
+
+impl Drop for HistoryFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn variant(demo: &str, after: Option<&str>) -> &'static Variant {
+    VARIANTS
+        .iter()
+        .find(|variant| variant.demo == demo && variant.after == after)
+        .unwrap()
+}
+
+// Using s3 after it moved into s4, which fails with E0382
+fn moved() -> &'static Variant {
+    variant("basic_string_moves", None)
+}
+
+// Printing t1 by itself, which compiles
+fn compiles() -> &'static Variant {
+    variant("borrowing_combos", Some("(1)"))
+}
+
+fn play(questions: &[&Variant], input: &str) -> (Scores, String) {
+    let mut out = Vec::new();
+    let scores = quiz::run(questions, input.as_bytes(), &mut out).unwrap();
+    (scores, String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn strip_comment_drops_a_trailing_comment() {
+    assert_eq!(quiz::strip_comment("let s = x; // moved"), "let s = x;");
+    assert_eq!(quiz::strip_comment("    // only a comment"), "");
+    assert_eq!(quiz::strip_comment("let s = x;"), "let s = x;");
+}
+
+#[test]
+fn strip_comment_keeps_slashes_inside_strings() {
+    let line = r#"println!("http://example.com"); // a link"#;
+    assert_eq!(
+        quiz::strip_comment(line),
+        r#"println!("http://example.com");"#
+    );
+
+    let escaped = r#"println!("a \" // b");"#;
+    assert_eq!(quiz::strip_comment(escaped), escaped);
+}
+
+#[test]
+fn normalise_code_accepts_any_spelling_of_the_code() {
+    assert_eq!(quiz::normalise_code("E0502"), "E0502");
+    assert_eq!(quiz::normalise_code("e0502"), "E0502");
+    assert_eq!(quiz::normalise_code("502"), "E0502");
+    assert_eq!(quiz::normalise_code("borrow"), "BORROW");
+}
+
+#[test]
+fn load_history_adds_up_every_session_per_topic() {
+    let file = HistoryFile::new(
+        "history",
+        "1,moves,1,2\n2,moves,2,3\n2,scopes,0,1\nnot a line\n3,nonsense,1,1\n4,moves,x,1\n",
+    );
+
+    let history = quiz::load_history(&file.path).unwrap();
+
+    assert_eq!(history.len(), 2);
+    assert_eq!(
+        history[&Topic::Moves],
+        Score {
+            correct: 3,
+            asked: 5
+        }
+    );
+    assert_eq!(
+        history[&Topic::Scopes],
+        Score {
+            correct: 0,
+            asked: 1
+        }
+    );
+}
+
+#[test]
+fn load_history_is_empty_without_a_file() {
+    let path = env::temp_dir().join("rusty-stuff-quiz-no-such-history.csv");
+    assert!(quiz::load_history(&path).unwrap().is_empty());
+}
+
+#[test]
+fn run_scores_answers_against_rustc() {
+    let (scores, output) = play(&[moved(), compiles()], "n\n382\ny\n");
+
+    assert!(output.contains("Question 1 (moves): basic_string_moves"));
+    assert!(output.contains("Correct! rustc reports E0382"));
+    assert!(output.contains("Correct! It compiles."));
+
+    let total: u32 = scores.values().map(|score| score.correct).sum();
+    assert_eq!(total, 2);
+    assert_eq!(scores[&moved().topic].asked, 1);
+}
+
+#[test]
+fn run_marks_a_wrong_answer() {
+    let (scores, output) = play(&[moved()], "maybe\ny\n");
+
+    assert!(output.contains("Please answer y or n"));
+    assert!(output.contains("Not quite. rustc reports E0382"));
+    assert_eq!(
+        scores[&Topic::Moves],
+        Score {
+            correct: 0,
+            asked: 1
+        }
+    );
+}
+
+#[test]
+fn run_stops_when_the_input_runs_out() {
+    let (scores, _) = play(&[moved(), compiles()], "n\n");
+
+    assert!(scores.is_empty());
+}
+
+#[test]
+fn run_skips_a_question_that_cannot_be_put() {
+    let missing = Variant {
+        demo: "no_such_demo",
+        topic: Topic::Scopes,
+        label: "a demo that isn't there",
+        after: None,
+        uncomment: &["println!"],
+    };
+
+    let (scores, output) = play(&[&missing, moved()], "n\nE0382\n");
+
+    assert!(output.contains("Skipping no_such_demo"));
+    assert!(!scores.contains_key(&Topic::Scopes));
+    assert_eq!(
+        scores[&Topic::Moves],
+        Score {
+            correct: 1,
+            asked: 1
+        }
+    );
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+
+use rusty_stuff::inventory::ShirtColour;
+use rusty_stuff::inventory::ledger::Entry;
+use rusty_stuff::inventory::report::{self, Depletion, ReportFormat, Section, Summary};
+
+mod common;
+
+use common::{giveaway, refused, restock, stocktake};
+
+// An event where red ran out and one user went without because of it
+fn entries() -> Vec<Entry> {
+    vec![
+        stocktake(
+            100,
+            &[("red/M/crew", 1), ("blue/M/crew", 2), ("blue/L/polo", 1)],
+        ),
+        giveaway(160, "ferris", "red/*/*", "red/M/crew"),
+        refused(200, "corro", "red/*/*"),
+        giveaway(220, "corro", "*/*/*", "blue/M/crew"),
+        giveaway(300, "bors", "*/L/*", "blue/L/polo"),
+        restock(400, "green/S/crew", 2),
+    ]
+}
+
+fn write(format: ReportFormat, sections: &[Section]) -> String {
+    let mut out = Vec::new();
+    report::report(&entries())
+        .write(format, sections, &mut out)
+        .unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn summary_counts_preferences_and_how_often_they_were_honoured() {
+    let summary = report::report(&entries()).summary;
+
+    assert_eq!(
+        summary,
+        Summary {
+            requests: 4,
+            stated: 3,
+            honoured: 2,
+            fallback: 1,
+            refused: 1,
+        }
+    );
+    assert_eq!(summary.honoured_rate(), Some(2.0 / 3.0));
+}
+
+#[test]
+fn no_stated_preferences_has_no_honoured_rate() {
+    assert_eq!(report::report(&[]).summary.honoured_rate(), None);
+}
+
+#[test]
+fn colours_show_how_far_each_ran_down() {
+    let colours = report::report(&entries()).colours;
+
+    let colour = |name: &str| ShirtColour::new(name);
+    assert_eq!(
+        colours,
+        [
+            Depletion {
+                colour: colour("blue"),
+                stocked: 3,
+                given: 2,
+                left: 1,
+                ran_out: None,
+            },
+            Depletion {
+                colour: colour("green"),
+                stocked: 2,
+                given: 0,
+                left: 2,
+                ran_out: None,
+            },
+            Depletion {
+                colour: colour("red"),
+                stocked: 1,
+                given: 1,
+                left: 0,
+                ran_out: Some(2),
+            },
+        ]
+    );
+    assert_eq!(colours[0].depleted(), 2.0 / 3.0);
+}
+
+#[test]
+fn a_restock_means_the_colour_has_not_run_out() {
+    let mut entries = entries();
+    entries.push(restock(500, "red/M/crew", 1));
+
+    let red = &report::report(&entries).colours[2];
+
+    assert_eq!((red.left, red.ran_out), (1, None));
+}
+
+#[test]
+fn a_stocktake_part_way_through_only_adds_what_it_found() {
+    let mut entries = entries();
+    // Two more blue polos turned up, and the blue crew neck went missing
+    entries.push(stocktake(500, &[("blue/L/polo", 2), ("green/S/crew", 2)]));
+    entries.push(giveaway(600, "crab", "blue/*/*", "blue/L/polo"));
+
+    let blue = &report::report(&entries).colours[0];
+
+    assert_eq!(
+        *blue,
+        Depletion {
+            colour: ShirtColour::new("blue"),
+            stocked: 5,
+            given: 3,
+            left: 1,
+            ran_out: None,
+        }
+    );
+    assert_eq!(blue.depleted(), 3.0 / 5.0);
+}
+
+#[test]
+fn timeline_has_the_stock_after_every_entry() {
+    let timeline = report::report(&entries()).timeline;
+
+    let totals: Vec<(usize, u64, u32)> = timeline
+        .iter()
+        .map(|level| (level.entry, level.time, level.total))
+        .collect();
+    assert_eq!(
+        totals,
+        [
+            (1, 100, 4),
+            (2, 160, 3),
+            (3, 200, 3),
+            (4, 220, 2),
+            (5, 300, 1),
+            (6, 400, 3)
+        ]
+    );
+    // Green is listed before it is stocked
+    assert_eq!(
+        timeline[0].colours,
+        BTreeMap::from([
+            (ShirtColour::new("blue"), 3),
+            (ShirtColour::new("green"), 0),
+            (ShirtColour::new("red"), 1),
+        ])
+    );
+}
+
+#[test]
+fn table_lines_up_the_columns() {
+    assert_eq!(
+        write(ReportFormat::Table, &[Section::Summary, Section::Colours]),
+        "    4  asked for a shirt\n\
+         \x20   3  stated a preference, 2 honoured (67%)\n\
+         \x20   1  took whatever was going\n\
+         \x20   1  went without\n\
+         \n\
+         colour  stocked  given  left  depleted  ran out\n\
+         blue          3      2     1       67%        -\n\
+         green         2      0     2        0%        -\n\
+         red           1      1     0      100%  entry 2\n"
+    );
+}
+
+#[test]
+fn csv_has_a_table_per_section() {
+    assert_eq!(
+        write(ReportFormat::Csv, &[Section::Summary, Section::Timeline]),
+        "requests,stated,honoured,fallback,refused,rate\n\
+         4,3,2,1,1,0.667\n\
+         \n\
+         entry,time,total,blue,green,red\n\
+         1,100,4,3,0,1\n\
+         2,160,3,3,0,0\n\
+         3,200,3,3,0,0\n\
+         4,220,2,2,0,0\n\
+         5,300,1,1,0,0\n\
+         6,400,3,1,2,0\n"
+    );
+}
+
+#[test]
+fn json_has_a_field_per_section() {
+    let json: serde_json::Value =
+        serde_json::from_str(&write(ReportFormat::Json, &Section::ALL)).unwrap();
+
+    assert_eq!(json["summary"]["refused"], 1);
+    assert_eq!(json["colours"][2]["colour"], "red");
+    assert_eq!(json["colours"][2]["ran_out"], 2);
+    assert_eq!(json["timeline"][5]["colours"]["green"], 2);
+}
+
+#[test]
+fn sections_are_named_for_the_command_line() {
+    assert_eq!("colours".parse(), Ok(Section::Colours));
+    assert_eq!(
+        "colors".parse::<Section>(),
+        Err(String::from(
+            "unknown section \"colors\", expected summary, colours or timeline"
+        ))
+    );
+}
@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use rusty_stuff::inventory::clock::ManualClock;
+use rusty_stuff::inventory::{GiveawayError, Inventory, Preference, ReservationError};
+
+mod common;
+
+use common::shirt;
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+// The book's two blue shirts and a red one, on a clock that only moves when told
+fn store() -> (Inventory, ManualClock) {
+    let clock = ManualClock::starting_at(1_000);
+    let mut store: Inventory = ["blue/M/crew", "red/M/crew", "blue/M/crew"]
+        .into_iter()
+        .map(shirt)
+        .collect();
+    store.set_clock(clock.clone());
+    (store, clock)
+}
+
+fn red() -> Preference {
+    Preference::any().colour("red")
+}
+
+#[test]
+fn a_reservation_holds_a_shirt_that_suits_the_preference() {
+    let (mut store, _) = store();
+
+    let reservation = store.reserve("ferris", &red(), 10 * MINUTE).unwrap();
+
+    assert_eq!(reservation.user, "ferris");
+    assert_eq!(reservation.shirt, shirt("red/M/crew"));
+    assert_eq!(reservation.expires, 1_000 + 600);
+    // Still on the shelf, but not for anyone else
+    assert_eq!(store.stock(&shirt("red/M/crew")), 1);
+    assert_eq!(store.held(&shirt("red/M/crew")), 1);
+    assert_eq!(
+        store.giveaway(&red()),
+        Err(GiveawayError::OutOfStock(red()))
+    );
+}
+
+#[test]
+fn confirming_hands_over_the_held_shirt() {
+    let (mut store, _) = store();
+    let id = store.reserve("ferris", &red(), 10 * MINUTE).unwrap().id;
+
+    let confirmed = store.confirm(id).unwrap();
+
+    assert_eq!(confirmed.shirt, shirt("red/M/crew"));
+    assert_eq!(store.stock(&shirt("red/M/crew")), 0);
+    assert_eq!(store.held(&shirt("red/M/crew")), 0);
+    assert_eq!(store.confirm(id), Err(ReservationError::NotHeld(id)));
+}
+
+#[test]
+fn cancelling_puts_the_shirt_back() {
+    let (mut store, _) = store();
+    let id = store.reserve("ferris", &red(), 10 * MINUTE).unwrap().id;
+
+    store.cancel(id).unwrap();
+
+    assert_eq!(store.giveaway(&red()), Ok(shirt("red/M/crew")));
+    assert_eq!(store.cancel(id), Err(ReservationError::NotHeld(id)));
+}
+
+#[test]
+fn expired_holds_return_to_stock_by_themselves() {
+    let (mut store, clock) = store();
+    let id = store.reserve("ferris", &red(), 10 * MINUTE).unwrap().id;
+
+    clock.advance(9 * MINUTE);
+    assert_eq!(store.held(&shirt("red/M/crew")), 1);
+
+    clock.advance(MINUTE);
+    assert_eq!(store.held(&shirt("red/M/crew")), 0);
+    assert!(store.reservations().is_empty());
+    assert_eq!(store.confirm(id), Err(ReservationError::NotHeld(id)));
+    assert_eq!(store.giveaway(&red()), Ok(shirt("red/M/crew")));
+}
+
+#[test]
+fn expire_reports_the_holds_that_ran_out() {
+    let (mut store, clock) = store();
+    let short = store.reserve("ferris", &red(), MINUTE).unwrap();
+    let long = store
+        .reserve("corro", &Preference::any(), 60 * MINUTE)
+        .unwrap();
+
+    clock.advance(2 * MINUTE);
+
+    assert_eq!(store.expire(), [short]);
+    assert_eq!(store.reservations(), [&long]);
+}
+
+#[test]
+fn the_fallback_only_considers_unreserved_stock() {
+    let (mut store, _) = store();
+    store.restock(shirt("red/M/crew"), 1).unwrap();
+
+    // Two of each, until both blues are held
+    store
+        .reserve("ferris", &Preference::any().colour("blue"), MINUTE)
+        .unwrap();
+    store
+        .reserve("corro", &Preference::any().colour("blue"), MINUTE)
+        .unwrap();
+
+    assert_eq!(store.most_stocked(), Some(shirt("red/M/crew")));
+    assert_eq!(store.giveaway(&Preference::any()), Ok(shirt("red/M/crew")));
+    assert_eq!(store.giveaway(&Preference::any()), Ok(shirt("red/M/crew")));
+    // Everything left is held
+    assert_eq!(
+        store.giveaway(&Preference::any()),
+        Err(GiveawayError::SoldOut)
+    );
+    assert_eq!(store.total(), 2);
+}
+
+#[test]
+fn nothing_can_be_reserved_twice() {
+    let (mut store, _) = store();
+
+    for user in ["ferris", "corro", "bors"] {
+        store.reserve(user, &Preference::any(), MINUTE).unwrap();
+    }
+
+    assert_eq!(
+        store.reserve("crab", &Preference::any(), MINUTE),
+        Err(ReservationError::Unavailable(GiveawayError::SoldOut))
+    );
+}
+
+#[test]
+fn holds_shorter_than_a_second_are_refused() {
+    let (mut store, _) = store();
+
+    for hold in [Duration::ZERO, Duration::from_millis(999)] {
+        assert_eq!(
+            store.reserve("ferris", &red(), hold),
+            Err(ReservationError::TooShort(hold))
+        );
+    }
+    assert_eq!(store.held(&shirt("red/M/crew")), 0);
+}
+
+#[test]
+fn confirming_a_shirt_taken_from_under_the_hold_fails() {
+    let (mut store, _) = store();
+    let id = store.reserve("ferris", &red(), MINUTE).unwrap().id;
+
+    assert!(store.take(&shirt("red/M/crew")));
+
+    assert_eq!(
+        store.confirm(id),
+        Err(ReservationError::Gone {
+            id,
+            shirt: shirt("red/M/crew")
+        })
+    );
+    // The hold is dropped, as it can't be honoured
+    assert_eq!(store.held(&shirt("red/M/crew")), 0);
+    assert_eq!(store.confirm(id), Err(ReservationError::NotHeld(id)));
+}
@@ -0,0 +1,314 @@
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+
+use serde_json::{Value, json};
+
+use rusty_stuff::inventory::Inventory;
+use rusty_stuff::inventory::ledger::Event;
+use rusty_stuff::inventory::server::Server;
+use rusty_stuff::inventory::shop::Shop;
+
+mod common;
+
+use common::shirt;
+
+// Serves the inventory on a free port for the rest of the test run
+fn start(server: Server) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || server.serve(listener));
+    address
+}
+
+fn start_with(stock: &[(&str, u32)]) -> SocketAddr {
+    let mut inventory = Inventory::new();
+    for &(sku, count) in stock {
+        inventory.restock(shirt(sku), count).unwrap();
+    }
+    start(Server::new(inventory))
+}
+
+// Sends raw bytes and returns the status code and JSON body of the response
+fn exchange(address: SocketAddr, raw: &[u8]) -> (u16, Value) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(raw).unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+fn request(address: SocketAddr, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let raw = format!(
+        "{method} {path} HTTP/1.1\r\nHost: {address}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    exchange(address, raw.as_bytes())
+}
+
+fn get_stock(address: SocketAddr) -> Value {
+    let (status, body) = request(address, "GET", "/stock", None);
+    assert_eq!(status, 200);
+    body
+}
+
+#[test]
+fn stock_lists_every_shirt_with_the_total() {
+    let address = start_with(&[("red/M/crew", 1), ("blue/L/polo", 2)]);
+
+    assert_eq!(
+        get_stock(address),
+        json!({
+            "total": 3,
+            "stock": [
+                {"shirt": "blue/L/polo", "count": 2, "held": 0},
+                {"shirt": "red/M/crew", "count": 1, "held": 0},
+            ]
+        })
+    );
+}
+
+#[test]
+fn giveaway_hands_out_a_shirt_that_suits_the_preference() {
+    let address = start_with(&[("red/M/crew", 1), ("blue/L/polo", 5)]);
+
+    let body = json!({"user": "ferris", "preference": "*/M/*"});
+    assert_eq!(
+        request(address, "POST", "/giveaway", Some(body)),
+        (200, json!({"shirt": "red/M/crew"}))
+    );
+    // No preference gets the most stocked
+    let body = json!({"user": "corro"});
+    assert_eq!(
+        request(address, "POST", "/giveaway", Some(body)),
+        (200, json!({"shirt": "blue/L/polo"}))
+    );
+    assert_eq!(get_stock(address)["total"], 4);
+}
+
+#[test]
+fn giveaway_of_a_shirt_that_has_run_out_is_a_conflict() {
+    let address = start_with(&[("red/M/crew", 1)]);
+
+    let body = json!({"user": "ferris", "preference": "*/XL/*"});
+    assert_eq!(
+        request(address, "POST", "/giveaway", Some(body)),
+        (409, json!({"error": "there are no */XL/* shirts left"}))
+    );
+}
+
+#[test]
+fn restock_adds_to_what_is_there() {
+    let address = start_with(&[("red/M/crew", 1)]);
+
+    let body = json!({"shirt": "Red/m/crew", "count": 4});
+    assert_eq!(
+        request(address, "POST", "/restock", Some(body)),
+        (200, json!({"shirt": "red/M/crew", "count": 5}))
+    );
+}
+
+#[test]
+fn reserved_shirts_are_held_until_confirmed() {
+    let address = start_with(&[("red/M/crew", 1), ("blue/L/polo", 1)]);
+
+    let body = json!({"user": "ferris", "preference": "red/*/*", "minutes": 10});
+    let (status, reservation) = request(address, "POST", "/reserve", Some(body));
+    assert_eq!(status, 200);
+    assert_eq!(reservation["shirt"], "red/M/crew");
+    let id = reservation["reservation"].clone();
+
+    assert_eq!(get_stock(address)["stock"][1]["held"], 1);
+    // Nobody else can have the held shirt in the meantime
+    let body = json!({"user": "corro", "preference": "red/*/*"});
+    assert_eq!(request(address, "POST", "/giveaway", Some(body)).0, 409);
+
+    let body = json!({"reservation": id});
+    assert_eq!(
+        request(address, "POST", "/confirm", Some(body.clone())),
+        (200, json!({"user": "ferris", "shirt": "red/M/crew"}))
+    );
+    assert_eq!(request(address, "POST", "/confirm", Some(body)).0, 409);
+    assert_eq!(get_stock(address)["total"], 1);
+}
+
+#[test]
+fn cancelled_reservations_go_back_to_stock() {
+    let address = start_with(&[("red/M/crew", 1)]);
+
+    let body = json!({"user": "ferris"});
+    let id = request(address, "POST", "/reserve", Some(body)).1["reservation"].clone();
+    let body = json!({"reservation": id});
+    assert_eq!(
+        request(address, "POST", "/cancel", Some(body)),
+        (200, json!({"cancelled": id}))
+    );
+
+    let body = json!({"user": "corro"});
+    assert_eq!(request(address, "POST", "/giveaway", Some(body)).0, 200);
+}
+
+#[test]
+fn bad_requests_are_refused() {
+    let address = start_with(&[("red/M/crew", 1)]);
+
+    let cases = [
+        (
+            "POST",
+            "/giveaway",
+            Some(json!({"preference": "*/M/*"})),
+            400,
+        ),
+        ("POST", "/giveaway", Some(json!({"user": " "})), 400),
+        (
+            "POST",
+            "/giveaway",
+            Some(json!({"user": "ferris", "preference": "red"})),
+            400,
+        ),
+        (
+            "POST",
+            "/restock",
+            Some(json!({"shirt": "red/M/crew", "count": -1})),
+            400,
+        ),
+        ("POST", "/restock", None, 400),
+        ("DELETE", "/stock", None, 405),
+        ("GET", "/giveaway", None, 405),
+        ("GET", "/shirts", None, 404),
+        ("POST", "/reserve", Some(json!({"user": ""})), 400),
+        (
+            "POST",
+            "/reserve",
+            Some(json!({"user": "ferris", "minutes": 0})),
+            400,
+        ),
+        ("POST", "/confirm", Some(json!({"reservation": "one"})), 400),
+        ("POST", "/cancel", Some(json!({"reservation": 99})), 409),
+    ];
+
+    for (method, path, body, expected) in cases {
+        let (status, response) = request(address, method, path, body.clone());
+        assert_eq!(status, expected, "{method} {path} {body:?}");
+        assert!(response["error"].is_string(), "{response}");
+    }
+
+    assert_eq!(exchange(address, b"\r\n\r\n").0, 400);
+    // A request line or headers that never end are cut off
+    let endless = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(64 * 1024));
+    assert_eq!(exchange(address, endless.as_bytes()).0, 431);
+    let headers = format!(
+        "GET /stock HTTP/1.1\r\n{}\r\n",
+        "X-Padding: a\r\n".repeat(4096)
+    );
+    assert_eq!(exchange(address, headers.as_bytes()).0, 431);
+    // None of that touched the stock
+    assert_eq!(get_stock(address)["total"], 1);
+}
+
+#[test]
+fn changes_are_saved_and_recorded_when_keeping_a_shop() {
+    let dir = env::temp_dir().join(format!("rusty-stuff-server-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let stock = dir.join("stock.json");
+    let ledger = dir.join("ledger.jsonl");
+
+    let address = start(Server::keeping(Shop::new(&stock, &ledger)).unwrap());
+    request(
+        address,
+        "POST",
+        "/restock",
+        Some(json!({"shirt": "red/M/crew", "count": 2})),
+    );
+    request(
+        address,
+        "POST",
+        "/giveaway",
+        Some(json!({"user": "ferris"})),
+    );
+    // Users who go without are recorded too
+    request(
+        address,
+        "POST",
+        "/giveaway",
+        Some(json!({"user": "corro", "preference": "blue/*/*"})),
+    );
+
+    let reopened = Shop::new(&stock, &ledger);
+    assert_eq!(reopened.load().unwrap().stock(&shirt("red/M/crew")), 1);
+    let entries = reopened.entries().unwrap();
+    assert_eq!(entries.len(), 3);
+    assert!(matches!(&entries[2].event, Event::Refused { user, .. } if user == "corro"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn changes_that_cannot_be_recorded_are_undone() {
+    let dir = env::temp_dir().join(format!(
+        "rusty-stuff-server-unrecorded-{}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let stock = dir.join("stock.json");
+    fs::write(
+        &stock,
+        r#"[{"colour": "red", "size": "M", "style": "crew", "count": 2}]"#,
+    )
+    .unwrap();
+
+    // A directory can't be appended to, so every change fails to be recorded
+    let address = start(Server::keeping(Shop::new(&stock, &dir)).unwrap());
+
+    let giveaway = Some(json!({"user": "ferris"}));
+    assert_eq!(request(address, "POST", "/giveaway", giveaway).0, 500);
+    let restock = Some(json!({"shirt": "red/M/crew", "count": 3}));
+    assert_eq!(request(address, "POST", "/restock", restock).0, 500);
+
+    let (status, reserved) = request(address, "POST", "/reserve", Some(json!({"user": "corro"})));
+    assert_eq!(status, 200);
+    let confirm = Some(json!({"reservation": reserved["reservation"]}));
+    assert_eq!(request(address, "POST", "/confirm", confirm).0, 500);
+
+    // Still both shirts, one still held for corro
+    assert_eq!(
+        get_stock(address)["stock"],
+        json!([{"shirt": "red/M/crew", "count": 2, "held": 1}])
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn clients_arriving_at_once_never_get_more_than_the_stock() {
+    let address = start_with(&[("red/M/crew", 20), ("blue/L/polo", 10)]);
+
+    let statuses: Vec<u16> = thread::scope(|scope| {
+        let clients: Vec<_> = (0..100)
+            .map(|i| {
+                scope.spawn(move || {
+                    let body = json!({"user": format!("user-{i}")});
+                    request(address, "POST", "/giveaway", Some(body)).0
+                })
+            })
+            .collect();
+
+        clients
+            .into_iter()
+            .map(|client| client.join().unwrap())
+            .collect()
+    });
+
+    assert_eq!(statuses.iter().filter(|&&status| status == 200).count(), 30);
+    assert_eq!(statuses.iter().filter(|&&status| status == 409).count(), 70);
+    assert_eq!(get_stock(address)["total"], 0);
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Barrier;
+use std::thread;
+
+use rusty_stuff::inventory::shared::SharedInventory;
+use rusty_stuff::inventory::{GiveawayError, Inventory, Preference, Shirt, Size};
+
+mod common;
+
+use common::shirt;
+
+const THREADS: usize = 400;
+
+fn stocked(stock: &[(&str, u32)]) -> SharedInventory {
+    let mut inventory = Inventory::new();
+    for &(sku, count) in stock {
+        inventory.restock(shirt(sku), count).unwrap();
+    }
+    SharedInventory::new(inventory)
+}
+
+// Every thread waits at the barrier and then tries for one shirt, so they all
+// go for the stock at the same moment
+fn rush(
+    inventory: &SharedInventory,
+    preference: impl Fn(usize) -> Preference + Sync,
+) -> Vec<Result<Shirt, GiveawayError>> {
+    let start = Barrier::new(THREADS);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|i| {
+                let (start, preference) = (&start, &preference);
+                scope.spawn(move || {
+                    start.wait();
+                    inventory.giveaway(&preference(i))
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+fn tally(given: &[Result<Shirt, GiveawayError>]) -> BTreeMap<String, u32> {
+    let mut tally = BTreeMap::new();
+    for shirt in given.iter().flatten() {
+        *tally.entry(shirt.to_string()).or_insert(0) += 1;
+    }
+    tally
+}
+
+#[test]
+fn hundreds_of_threads_never_get_more_than_the_stock() {
+    let stock = [
+        ("red/M/crew", 40),
+        ("blue/L/crew", 35),
+        ("green/S/polo", 25),
+    ];
+    let inventory = stocked(&stock);
+
+    let given = rush(&inventory, |_| Preference::any());
+
+    // Exactly the stock was handed out, each shirt as often as it was stocked
+    let expected: BTreeMap<String, u32> = stock
+        .iter()
+        .map(|&(sku, n)| (shirt(sku).to_string(), n))
+        .collect();
+    assert_eq!(tally(&given), expected);
+    assert_eq!(
+        given.iter().filter(|result| result.is_err()).count(),
+        THREADS - 100
+    );
+    assert!(
+        given
+            .iter()
+            .filter_map(|result| result.as_ref().err())
+            .all(|e| *e == GiveawayError::SoldOut)
+    );
+    assert_eq!(inventory.total(), 0);
+}
+
+#[test]
+fn threads_with_different_preferences_only_share_what_suits_them() {
+    let inventory = stocked(&[("red/M/crew", 30), ("red/L/crew", 30), ("blue/M/crew", 30)]);
+
+    // Half want any medium, the rest want anything red
+    let given = rush(&inventory, |i| {
+        if i % 2 == 0 {
+            Preference::any().size(Size::M)
+        } else {
+            Preference::any().colour("red")
+        }
+    });
+
+    assert_eq!(given.iter().flatten().count(), 90);
+    assert_eq!(inventory.total(), 0);
+    assert!(
+        given
+            .iter()
+            .flatten()
+            .all(|shirt| shirt.size == Size::M || shirt.colour.name() == "red")
+    );
+}
+
+#[test]
+fn restocking_while_giving_away_loses_nothing() {
+    let inventory = stocked(&[("red/M/crew", 50)]);
+    let restocks = 20;
+
+    let given = thread::scope(|scope| {
+        let givers: Vec<_> = (0..THREADS)
+            .map(|_| scope.spawn(|| inventory.giveaway(&Preference::any()).is_ok()))
+            .collect();
+        for _ in 0..restocks {
+            scope.spawn(|| inventory.restock(shirt("red/M/crew"), 5).unwrap());
+        }
+
+        givers
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(|&given| given)
+            .count() as u32
+    });
+
+    // Whatever order things happened in, every shirt is either given or left
+    assert_eq!(given + inventory.total(), 50 + restocks * 5);
+}
+
+#[test]
+fn clones_share_one_stock() {
+    let inventory = stocked(&[("red/M/crew", 1)]);
+    let clone = inventory.clone();
+
+    assert_eq!(clone.giveaway(&Preference::any()), Ok(shirt("red/M/crew")));
+    assert_eq!(
+        inventory.giveaway(&Preference::any()),
+        Err(GiveawayError::SoldOut)
+    );
+}
+
+#[test]
+fn a_closure_that_panics_leaves_the_stock_as_it_was() {
+    let inventory = stocked(&[("red/M/crew", 2)]);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        inventory.with(|inventory| {
+            inventory.take(&shirt("red/M/crew"));
+            inventory.restock(shirt("blue/M/crew"), 5).unwrap();
+            panic!("part way through");
+        })
+    }));
+
+    assert!(result.is_err());
+    assert_eq!(inventory.stock(&shirt("red/M/crew")), 2);
+    assert_eq!(inventory.stock(&shirt("blue/M/crew")), 0);
+    assert_eq!(inventory.total(), 2);
+}
@@ -0,0 +1,447 @@
+// Golden output tests for the demo binaries. Each case runs a binary, captures
+// its exit status, stdout and stderr, and compares them with a file under
+// tests/snapshots. To accept new output after changing a demo, run
+//
+//     UPDATE_SNAPSHOTS=1 cargo test --test snapshots
+//
+// and review the changes to the .snap files like any other diff.
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+struct Captured {
+    status: ExitStatus,
+    stdout: String,
+    stderr: String,
+}
+
+// A fresh working directory per case, since some demos create files in it
+struct WorkDir {
+    path: PathBuf,
+}
+
+impl WorkDir {
+    fn new(name: &str) -> WorkDir {
+        let path = env::temp_dir().join(format!(
+            "rusty-stuff-snapshot-{}-{name}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        WorkDir { path }
+    }
+
+    fn with_file(self, name: &str, contents: &str) -> WorkDir {
+        fs::write(self.path.join(name), contents).unwrap();
+        self
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn command(binary: &str, args: &[&str], dir: &WorkDir) -> Command {
+    let mut command = Command::new(binary);
+    command
+        .args(args)
+        .current_dir(&dir.path)
+        .env("RUST_BACKTRACE", "0");
+    command
+}
+
+fn run(mut command: Command, stdin: &str) -> Captured {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    Captured {
+        status: output.status,
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    }
+}
+
+// Signals only exist on unix; elsewhere a panic=abort shows up as an exit code
+#[cfg(unix)]
+fn describe_status(status: ExitStatus) -> String {
+    use std::os::unix::process::ExitStatusExt;
+
+    match (status.code(), status.signal()) {
+        (Some(code), _) => format!("exit code {code}"),
+        (None, Some(signal)) => format!("killed by signal {signal}"),
+        (None, None) => String::from("unknown"),
+    }
+}
+
+#[cfg(not(unix))]
+fn describe_status(status: ExitStatus) -> String {
+    match status.code() {
+        Some(code) => format!("exit code {code}"),
+        None => String::from("unknown"),
+    }
+}
+
+// Panic messages name the thread with its OS id, as in
+// "thread 'main' (12345) panicked at ...", which changes from run to run
+fn redact_thread_id(line: &str) -> String {
+    if let Some(rest) = line.strip_prefix("thread '")
+        && let Some((name, after)) = rest.split_once("' (")
+        && let Some((id, message)) = after.split_once(") ")
+        && id.chars().all(|c| c.is_ascii_digit())
+    {
+        return format!("thread '{name}' {message}");
+    }
+
+    line.to_string()
+}
+
+fn redact(text: &str) -> String {
+    text.lines()
+        .map(|line| redact_thread_id(line) + "\n")
+        .collect()
+}
+
+fn render(args: &[&str], captured: &Captured) -> String {
+    format!(
+        "args: {}\nstatus: {}\n--- stdout\n{}--- stderr\n{}",
+        args.join(" "),
+        describe_status(captured.status),
+        redact(&captured.stdout),
+        redact(&captured.stderr)
+    )
+}
+
+// A line diff of expected against actual, via the longest common subsequence
+fn diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out.push_str(&format!("  {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            out.push_str(&format!("+ {}\n", new[j]));
+            j += 1;
+        } else {
+            out.push_str(&format!("- {}\n", old[i]));
+            i += 1;
+        }
+    }
+
+    out
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.snap"));
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "No snapshot at {}; run with UPDATE_SNAPSHOTS=1 to create it. Output was:\n{actual}",
+            path.display()
+        )
+    });
+
+    assert!(
+        expected == actual,
+        "{name} no longer matches {} (- expected, + actual):\n{}\nRun with UPDATE_SNAPSHOTS=1 to accept the new output.",
+        path.display(),
+        diff(&expected, actual)
+    );
+}
+
+fn check_in(dir: WorkDir, name: &str, binary: &str, args: &[&str], stdin: &str) {
+    let captured = run(command(binary, args, &dir), stdin);
+    assert_snapshot(name, &render(args, &captured));
+}
+
+fn check(name: &str, binary: &str, args: &[&str]) {
+    check_in(WorkDir::new(name), name, binary, args, "");
+}
+
+#[test]
+fn rusty_list() {
+    check("rusty_list", env!("CARGO_BIN_EXE_rusty"), &["list"]);
+}
+
+#[test]
+fn rusty_run_all() {
+    check(
+        "rusty_run_all",
+        env!("CARGO_BIN_EXE_rusty"),
+        &["run", "--all"],
+    );
+}
+
+#[test]
+fn rusty_run_trace() {
+    let args = [
+        "run",
+        "--trace",
+        "ownership_and_functions",
+        "return_values_and_scope",
+    ];
+    check("rusty_run_trace", env!("CARGO_BIN_EXE_rusty"), &args);
+}
+
+#[test]
+fn rusty_run_trace_untraced_demo() {
+    let args = [
+        "run",
+        "--trace",
+        "ownership_and_functions",
+        "multiple_scopes",
+    ];
+    check(
+        "rusty_run_trace_untraced_demo",
+        env!("CARGO_BIN_EXE_rusty"),
+        &args,
+    );
+}
+
+#[test]
+fn rusty_unknown_demo() {
+    let args = ["run", "no_such_demo"];
+    check("rusty_unknown_demo", env!("CARGO_BIN_EXE_rusty"), &args);
+}
+
+#[test]
+fn rpl_closures_1_free_t_shirt() {
+    let binary = env!("CARGO_BIN_EXE_rpl_closures_1-free_t_shirt");
+    check("rpl_closures_1-free_t_shirt", binary, &[]);
+}
+
+#[test]
+fn rpl_closures_2() {
+    check("rpl_closures_2", env!("CARGO_BIN_EXE_rpl_closures_2"), &[]);
+}
+
+#[test]
+fn rpl_closures_3_capture_modes() {
+    let binary = env!("CARGO_BIN_EXE_rpl_closures_3-capture_modes");
+    check("rpl_closures_3-capture_modes", binary, &[]);
+}
+
+#[test]
+fn rpl_error_handling_1() {
+    let binary = env!("CARGO_BIN_EXE_rpl_error_handling_1");
+    check("rpl_error_handling_1", binary, &[]);
+}
+
+#[test]
+fn rpl_error_handling_2() {
+    let name = "rpl_error_handling_2";
+    let dir = WorkDir::new(name).with_file("hello.txt", "Ferris\n");
+    check_in(
+        dir,
+        name,
+        env!("CARGO_BIN_EXE_rpl_error_handling_2"),
+        &[],
+        "",
+    );
+}
+
+#[test]
+fn rpl_error_handling_2_without_hello_txt() {
+    let binary = env!("CARGO_BIN_EXE_rpl_error_handling_2");
+    check("rpl_error_handling_2_without_hello_txt", binary, &[]);
+}
+
+#[test]
+fn rpl_panic() {
+    check("rpl_panic", env!("CARGO_BIN_EXE_rpl_panic"), &[]);
+}
+
+#[test]
+fn rpl_variables() {
+    check("rpl_variables", env!("CARGO_BIN_EXE_rpl_variables"), &[]);
+}
+
+// The secret only shows with --debug, so a debug run with the same seed finds
+// it out, then the game is played for real around it. `before` answers the
+// menu, if the args leave it to ask, and `guesses` gives the guesses to make.
+fn play_guessing_game_in(
+    dir: WorkDir,
+    name: &str,
+    args: &[&str],
+    before: &str,
+    guesses: impl Fn(&str) -> String,
+) {
+    let binary = env!("CARGO_BIN_EXE_rpl_guessing_game");
+
+    let debug_args = [args, &["--debug"]].concat();
+    let debug = run(command(binary, &debug_args, &dir), before);
+    let secret = debug
+        .stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("[debug] The secret number is: "))
+        .expect("a debug game shows the secret")
+        .to_string();
+
+    let mut captured = run(
+        command(binary, args, &dir),
+        &(before.to_string() + &guesses(&secret)),
+    );
+    // Which number a seed gives is up to rand, so keep it out of the snapshot
+    captured.stdout = captured
+        .stdout
+        .replace(&format!(": {secret}\n"), ": [secret]\n")
+        .replace(&format!(" was {secret}."), " was [secret].");
+    assert_snapshot(name, &render(args, &captured));
+}
+
+fn play_guessing_game(name: &str, args: &[&str], before: &str, guesses: impl Fn(&str) -> String) {
+    play_guessing_game_in(WorkDir::new(name), name, args, before, guesses);
+}
+
+// Normal difficulty from the menu, then an invalid guess, one too small, one
+// too big and a win
+#[test]
+fn rpl_guessing_game() {
+    let args = ["--seed", "42"];
+    play_guessing_game("rpl_guessing_game", &args, "2\n\n", |secret| {
+        format!("ten\n0\n101\n{secret}\n")
+    });
+}
+
+#[test]
+fn rpl_guessing_game_out_of_guesses() {
+    let args = ["--easy", "--attempts", "2", "--seed", "42"];
+    play_guessing_game("rpl_guessing_game_out_of_guesses", &args, "", |_| {
+        String::from("0\n11\n")
+    });
+}
+
+#[test]
+fn rpl_guessing_game_custom_range_from_menu() {
+    let args = ["--attempts", "1", "--seed", "42"];
+    play_guessing_game(
+        "rpl_guessing_game_custom_range_from_menu",
+        &args,
+        "5\n4\n9-5\n5-9\n",
+        |secret| format!("{secret}\n"),
+    );
+}
+
+#[test]
+fn rpl_guessing_game_debug() {
+    let args = ["--hard", "--seed", "42", "--debug"];
+    play_guessing_game("rpl_guessing_game_debug", &args, "", |secret| {
+        format!("{secret}\n")
+    });
+}
+
+// Earlier scores, one unreadable line and one cut short, which the new score
+// must not be joined onto. Games with --seed aren't scored, so the secret is
+// pinned by a range of one number instead.
+#[test]
+fn rpl_guessing_game_high_scores() {
+    let name = "rpl_guessing_game_high_scores";
+    let scores = "\
+1700000000,3-3,2,4000
+1700000001,3-3,5,1500
+not,a,score,at all
+1700000002,normal,1,100
+1700000003,3-3,4,65000
+1700000004,3-3,3,";
+    let dir = WorkDir::new(name).with_file("guessing_game_scores.csv", scores);
+    let args = ["--range", "3-3"];
+    play_guessing_game_in(dir, name, &args, "", |secret| format!("1\n2\n{secret}\n"));
+}
+
+#[test]
+fn rpl_guessing_game_bad_range() {
+    let args = ["--range", "9-1"];
+    check(
+        "rpl_guessing_game_bad_range",
+        env!("CARGO_BIN_EXE_rpl_guessing_game"),
+        &args,
+    );
+}
+
+// Several runs in one working directory, for tools that keep state between runs
+fn check_session(dir: WorkDir, name: &str, binary: &str, runs: &[&[&str]]) {
+    let rendered: Vec<String> = runs
+        .iter()
+        .map(|args| render(args, &run(command(binary, args, &dir), "")))
+        .collect();
+    assert_snapshot(name, &rendered.join("\n"));
+}
+
+#[test]
+fn shirts_session() {
+    let name = "shirts_session";
+    let dir = WorkDir::new(name)
+        .with_file(
+            "sheet.csv",
+            "colour,size,style,count\nred,M,crew,1\nblue,M,crew,2\nblue,XL,v-neck,1\n",
+        )
+        .with_file(
+            "bad.csv",
+            "colour,size,style,count\nred,M,crew,1\n\nblue,XXXL,crew,2\n",
+        );
+
+    check_session(
+        dir,
+        name,
+        env!("CARGO_BIN_EXE_shirts"),
+        &[
+            &["stock"],
+            &["restock", "green/L/polo", "3"],
+            &["import", "bad.csv"],
+            &["import", "sheet.csv"],
+            &["giveaway", "ferris", "red/*/*"],
+            &["giveaway", "corro", "red/*/*"],
+            &["giveaway", "corro", "*/M/*"],
+            &["giveaway", "*/M/*"],
+            &["restock", "green/L/polo", "3"],
+            &["stock"],
+            &["replay", "--entry", "2"],
+            &["replay"],
+            &["audit"],
+            &["report", "summary"],
+            &["report", "colours"],
+            &["report", "--csv", "colours"],
+            &["report", "--json", "summary"],
+            &["report", "weather"],
+        ],
+    );
+}
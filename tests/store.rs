@@ -0,0 +1,204 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusty_stuff::inventory::store::{self, Format, StoreError};
+use rusty_stuff::inventory::{Inventory, Preference, Shirt};
+
+mod common;
+
+use common::shirt;
+
+fn stock() -> Inventory {
+    let mut inventory = Inventory::new();
+    inventory.restock(shirt("red/M/crew"), 10).unwrap();
+    inventory.restock(shirt("blue/XL/v-neck"), 4).unwrap();
+    inventory.restock(shirt("green/S/polo"), 0).unwrap();
+    inventory
+}
+
+fn levels(inventory: &Inventory) -> Vec<(Shirt, u32)> {
+    inventory
+        .stock_levels()
+        .map(|(shirt, count)| (shirt.clone(), count))
+        .collect()
+}
+
+fn invalid_line(result: Result<Vec<(Shirt, u32)>, StoreError>) -> (usize, String) {
+    match result {
+        Err(StoreError::Invalid { line, message }) => (line, message),
+        other => panic!("expected an invalid sheet, got {other:?}"),
+    }
+}
+
+// A scratch directory per test, removed afterwards
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> TempDir {
+        let path = env::temp_dir().join(format!("rusty-stuff-store-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    fn join(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn csv_sheets_list_a_shirt_per_row() {
+    let mut out = Vec::new();
+    store::write(&stock(), Format::Csv, &mut out).unwrap();
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "colour,size,style,count\n\
+         blue,XL,v-neck,4\n\
+         green,S,polo,0\n\
+         red,M,crew,10\n"
+    );
+}
+
+#[test]
+fn sheets_read_back_what_was_written() {
+    for format in [Format::Json, Format::Csv] {
+        let mut out = Vec::new();
+        store::write(&stock(), format, &mut out).unwrap();
+
+        let read = store::read(&String::from_utf8(out).unwrap(), format).unwrap();
+        assert_eq!(read, levels(&stock()), "{format:?}");
+    }
+}
+
+#[test]
+fn spreadsheet_padding_and_case_are_ignored() {
+    let sheet = "colour , size , style , count\n Red , m , Crew , 3\n";
+
+    assert_eq!(
+        store::read(sheet, Format::Csv).unwrap(),
+        [(shirt("red/M/crew"), 3)]
+    );
+}
+
+#[test]
+fn csv_errors_report_the_line() {
+    let sheet = "colour,size,style,count\nred,M,crew,1\nblue,XXXL,crew,2\n";
+    let (line, message) = invalid_line(store::read(sheet, Format::Csv));
+    assert_eq!(line, 3);
+    assert!(message.contains("unknown size \"XXXL\""), "{message}");
+
+    let sheet = "colour,size,style,count\nred,M,crew,1\nblue,M,crew,-2\n";
+    let (line, message) = invalid_line(store::read(sheet, Format::Csv));
+    assert_eq!(line, 3);
+    assert!(message.starts_with("column 4:"), "{message}");
+
+    let sheet = "colour,size,style,count\nred,M,crew,1\n\nblue,M\n";
+    let (line, message) = invalid_line(store::read(sheet, Format::Csv));
+    assert_eq!(line, 4);
+    assert_eq!(message, "expected 4 columns but found 2");
+}
+
+#[test]
+fn json_errors_report_the_line() {
+    let sheet = r#"[
+        {"colour": "red", "size": "M", "style": "crew", "count": 1},
+        {"colour": " ", "size": "M", "style": "crew", "count": 1}
+    ]"#;
+    assert_eq!(
+        invalid_line(store::read(sheet, Format::Json)),
+        (3, String::from("the colour is blank"))
+    );
+
+    let sheet = r#"[
+        {"colour": "red", "size": "M", "style": "crew"}
+    ]"#;
+    assert_eq!(
+        invalid_line(store::read(sheet, Format::Json)),
+        (2, String::from("missing field `count`"))
+    );
+}
+
+#[test]
+fn saved_stock_loads_again() {
+    let dir = TempDir::new("round-trip");
+
+    for name in ["stock.json", "stock.csv"] {
+        let path = dir.join(name);
+        store::save(&stock(), &path).unwrap();
+
+        let mut loaded = store::load(&path).unwrap();
+        assert_eq!(levels(&loaded), levels(&stock()), "{name}");
+        assert_eq!(loaded.giveaway(&Preference::any()), Ok(shirt("red/M/crew")));
+    }
+}
+
+#[test]
+fn a_shirt_listed_twice_is_counted_twice() {
+    let dir = TempDir::new("twice");
+    let path = dir.join("sheet.csv");
+    fs::write(
+        &path,
+        "colour,size,style,count\nred,M,crew,2\nRED,M,crew,3\n",
+    )
+    .unwrap();
+
+    assert_eq!(store::load(&path).unwrap().stock(&shirt("red/M/crew")), 5);
+}
+
+#[test]
+fn a_shirt_listed_twice_past_what_can_be_counted_reports_the_line() {
+    let dir = TempDir::new("too-many");
+    let csv = dir.join("sheet.csv");
+    fs::write(
+        &csv,
+        "colour,size,style,count\nred,M,crew,2\n\nblue,M,crew,1\nred,M,crew,4294967295\n",
+    )
+    .unwrap();
+    let json = dir.join("sheet.json");
+    fs::write(
+        &json,
+        r#"[
+        {"colour": "red", "size": "M", "style": "crew", "count": 2},
+        {
+            "colour": "red", "size": "M", "style": "crew", "count": 4294967295
+        }
+    ]"#,
+    )
+    .unwrap();
+
+    for (path, line) in [(csv, 5), (json, 3)] {
+        match store::load(&path) {
+            Err(StoreError::Invalid {
+                line: reported,
+                message,
+            }) => {
+                assert_eq!(reported, line, "{}", path.display());
+                assert!(
+                    message.starts_with("can't add 4294967295 red/M/crew"),
+                    "{message}"
+                );
+            }
+            other => panic!("expected line {line} to be invalid, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn only_json_and_csv_sheets_are_understood() {
+    assert_eq!(
+        Format::from_path(Path::new("stock.JSON")),
+        Some(Format::Json)
+    );
+    assert_eq!(Format::from_path(Path::new("stock.xlsx")), None);
+
+    let result = store::save(&stock(), Path::new("stock.xlsx"));
+    assert!(matches!(result, Err(StoreError::UnknownFormat(_))));
+}
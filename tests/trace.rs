@@ -0,0 +1,170 @@
+use rusty_stuff::quiz;
+use rusty_stuff::sharing_and_ownership::{self, DEMOS};
+use rusty_stuff::trace::{self, Traced};
+use rusty_stuff::variants;
+
+// The code of a demo without its signature, comments or blank lines, and with
+// a traced demo's Strings turned back into plain ones: `Traced::new("s", "hi")`
+// into `String::from("hi")`, and without the `.move_to(..)` calls
+fn code(name: &str) -> Vec<String> {
+    let source = variants::demo_source(name).unwrap();
+    source
+        .lines()
+        .skip(1)
+        .map(|line| untrace(quiz::strip_comment(line).trim()))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn untrace(line: &str) -> String {
+    let mut line = line.to_string();
+    while let Some(start) = line.find(".move_to(\"") {
+        let end = start + line[start..].find("\")").unwrap() + "\")".len();
+        line.replace_range(start..end, "");
+    }
+    while let Some(start) = line.find("Traced::new(\"") {
+        let end = start + line[start..].find(", ").unwrap() + ", ".len();
+        line.replace_range(start..end, "String::from(");
+    }
+    line.replace("Traced", "String")
+}
+
+// Each traced line without the source location on the end
+fn events(lines: Vec<String>) -> Vec<String> {
+    lines
+        .into_iter()
+        .map(|line| match line.rsplit_once(" at ") {
+            Some((event, _)) => event.to_string(),
+            None => line,
+        })
+        .collect()
+}
+
+#[test]
+fn a_value_is_dropped_at_the_end_of_its_scope() {
+    let lines = trace::capture(|| {
+        let s = Traced::new("s", "hello");
+        assert_eq!(s.len(), 5);
+    });
+
+    assert_eq!(
+        events(lines),
+        [
+            "s comes into scope",
+            "s goes out of scope and is dropped, freeing \"hello\""
+        ]
+    );
+}
+
+#[test]
+fn only_the_new_owner_drops_a_moved_value() {
+    let lines = trace::capture(|| {
+        let s1 = Traced::new("s1", "hello");
+        let s2 = s1.move_to("s2");
+        assert_eq!(s2.name(), "s2");
+    });
+
+    assert_eq!(
+        events(lines),
+        [
+            "s1 comes into scope",
+            "s1 moves to s2",
+            "s2 goes out of scope and is dropped, freeing \"hello\""
+        ]
+    );
+}
+
+#[test]
+fn a_clone_is_dropped_separately() {
+    let lines = trace::capture(|| {
+        let s1 = Traced::new("s1", "hello");
+        let s2 = s1.clone_as("s2");
+        drop(s1);
+        assert_eq!(s2.to_string(), "hello");
+    });
+
+    assert_eq!(
+        events(lines),
+        [
+            "s1 comes into scope",
+            "s1 is cloned into s2",
+            "s1 goes out of scope and is dropped, freeing \"hello\"",
+            "s2 goes out of scope and is dropped, freeing \"hello\""
+        ]
+    );
+}
+
+#[test]
+fn borrows_are_traced_but_printing_is_not() {
+    let lines = trace::capture(|| {
+        let s = Traced::new("s", "hello");
+        let r = s.lend();
+        let _ = format!("{r} {s}");
+        std::mem::forget(s);
+    });
+
+    assert_eq!(events(lines), ["s comes into scope", "s is borrowed"]);
+}
+
+#[test]
+fn the_trace_gives_the_calling_line() {
+    let line = line!() + 1;
+    let lines = trace::capture(|| std::mem::forget(Traced::new("s", "hello")));
+
+    assert_eq!(
+        lines,
+        [format!("s comes into scope at tests/trace.rs:{line}")]
+    );
+}
+
+#[test]
+fn captures_nest() {
+    let mut inner = Vec::new();
+    let outer = trace::capture(|| {
+        let s = Traced::new("outer", "a");
+        inner = trace::capture(|| drop(Traced::new("inner", "b")));
+        drop(s);
+    });
+
+    assert_eq!(outer.len(), 2);
+    assert_eq!(inner.len(), 2);
+    assert!(inner.iter().all(|line| line.starts_with("inner ")));
+}
+
+#[test]
+fn the_traced_demos_match_their_comments() {
+    let run = sharing_and_ownership::traced("return_values_and_scope").unwrap();
+
+    assert_eq!(
+        events(trace::capture(run)),
+        [
+            "some_string comes into scope",
+            "some_string moves to s1",
+            "s2 comes into scope",
+            "s2 moves to a_string",
+            "a_string moves to s3",
+            "s3 goes out of scope and is dropped, freeing \"hello\"",
+            "s1 goes out of scope and is dropped, freeing \"yours\""
+        ]
+    );
+}
+
+#[test]
+fn the_traced_demos_follow_the_demos_line_for_line() {
+    let traced: Vec<&str> = DEMOS
+        .iter()
+        .map(|demo| demo.name)
+        .filter(|name| sharing_and_ownership::traced(name).is_some())
+        .collect();
+    assert_eq!(traced.len(), 2);
+
+    for name in traced {
+        assert_eq!(code(&format!("{name}_traced")), code(name), "{name}");
+    }
+}
+
+#[test]
+fn only_some_demos_are_traced() {
+    assert!(sharing_and_ownership::traced("ownership_and_functions").is_some());
+    assert!(sharing_and_ownership::traced("multiple_scopes").is_none());
+}